@@ -0,0 +1,307 @@
+use crate::Float;
+use ndarray::prelude::*;
+use std::cell::{Ref, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+mod ops;
+
+/// Computes the gradient of each parent given the gradient of the output, the output itself and
+/// the parents of the operation.
+type BackwardFn<F> = Box<dyn Fn(&ArrayD<F>, &ArrayD<F>, &[Tensor<F>]) -> Vec<ArrayD<F>>>;
+
+struct Operation<F> {
+    parents: Vec<Tensor<F>>,
+    backward: BackwardFn<F>,
+}
+
+struct Inner<F> {
+    data: RefCell<ArrayD<F>>,
+    grad: RefCell<Option<ArrayD<F>>>,
+    requires_grad: bool,
+    operation: Option<Operation<F>>,
+}
+
+/// Array that records the operations applied to it on a tape, so that the gradient of any result
+/// with respect to its inputs can be computed with [`Tensor::backward`].
+///
+/// Cloning a tensor is cheap, as it only clones a reference to the same node of the graph.
+pub struct Tensor<F = f64> {
+    inner: Rc<Inner<F>>,
+}
+
+impl<F> Clone for Tensor<F> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: Rc::clone(&self.inner),
+        }
+    }
+}
+
+impl<F: Float> Tensor<F> {
+    #[inline]
+    #[must_use]
+    fn leaf(data: ArrayD<F>, requires_grad: bool) -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                data: RefCell::new(data),
+                grad: RefCell::new(None),
+                requires_grad,
+                operation: None,
+            }),
+        }
+    }
+
+    /// Creates a constant tensor, whose gradient is never computed.
+    #[inline]
+    #[must_use]
+    pub fn new<D: Dimension>(data: Array<F, D>) -> Self {
+        Self::leaf(data.into_dyn(), false)
+    }
+
+    /// Creates a tensor whose gradient gets accumulated in [`Tensor::grad`] on every backward pass.
+    #[inline]
+    #[must_use]
+    pub fn new_requires_grad<D: Dimension>(data: Array<F, D>) -> Self {
+        Self::leaf(data.into_dyn(), true)
+    }
+
+    /// Records the result of an operation on the tape. The operation is only kept if any of the
+    /// parents requires a gradient.
+    #[inline]
+    #[must_use]
+    pub(crate) fn from_operation<B>(data: ArrayD<F>, parents: Vec<Tensor<F>>, backward: B) -> Self
+    where
+        B: Fn(&ArrayD<F>, &ArrayD<F>, &[Tensor<F>]) -> Vec<ArrayD<F>> + 'static,
+    {
+        let requires_grad = parents.iter().any(Tensor::requires_grad);
+        let operation = requires_grad.then(|| Operation {
+            parents,
+            backward: Box::new(backward),
+        });
+
+        Self {
+            inner: Rc::new(Inner {
+                data: RefCell::new(data),
+                grad: RefCell::new(None),
+                requires_grad,
+                operation,
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn data(&self) -> Ref<'_, ArrayD<F>> {
+        self.inner.data.borrow()
+    }
+
+    #[inline]
+    pub fn shape(&self) -> Vec<usize> {
+        self.data().shape().to_vec()
+    }
+
+    /// Gradient accumulated by the backward passes, only available for tensors created with
+    /// [`Tensor::new_requires_grad`].
+    #[inline]
+    pub fn grad(&self) -> Option<ArrayD<F>> {
+        self.inner.grad.borrow().clone()
+    }
+
+    #[inline]
+    pub fn requires_grad(&self) -> bool {
+        self.inner.requires_grad
+    }
+
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.inner.operation.is_none()
+    }
+
+    #[inline]
+    pub fn zero_grad(&self) {
+        if let Some(grad) = self.inner.grad.borrow_mut().as_mut() {
+            grad.fill(F::zero());
+        }
+    }
+
+    /// Mutable access to the data and gradient of the tensor. Returns `None` while any other
+    /// reference to the tensor exists, e.g. the graph of a forward pass that has not been dropped.
+    #[inline]
+    pub fn parts_mut(&mut self) -> Option<(&mut ArrayD<F>, &mut ArrayD<F>)> {
+        let inner = Rc::get_mut(&mut self.inner)?;
+        let data = inner.data.get_mut();
+        let grad = inner
+            .grad
+            .get_mut()
+            .get_or_insert_with(|| ArrayD::zeros(data.raw_dim()));
+        Some((data, grad))
+    }
+
+    /// Returns a tensor with the same data that is not recorded on the tape.
+    #[inline]
+    #[must_use]
+    pub fn detach(&self) -> Self {
+        Self::new(self.data().clone())
+    }
+
+    #[inline]
+    fn id(&self) -> *const Inner<F> {
+        Rc::as_ptr(&self.inner)
+    }
+
+    /// Topological order of the graph, where every tensor appears after its parents.
+    fn topological_order(&self) -> Vec<Tensor<F>> {
+        let mut order = Vec::new();
+        let mut visited = HashSet::new();
+        // Iterative post-order traversal to not overflow the stack on deep graphs
+        let mut stack = vec![(self.clone(), false)];
+
+        while let Some((tensor, expanded)) = stack.pop() {
+            if expanded {
+                order.push(tensor);
+                continue;
+            }
+            if !visited.insert(tensor.id()) {
+                continue;
+            }
+
+            stack.push((tensor.clone(), true));
+            if let Some(operation) = &tensor.inner.operation {
+                operation
+                    .parents
+                    .iter()
+                    .filter(|parent| parent.requires_grad() && !visited.contains(&parent.id()))
+                    .for_each(|parent| stack.push((parent.clone(), false)));
+            }
+        }
+        order
+    }
+
+    /// Computes the gradient of the sum of the tensor with respect to every leaf that requires it.
+    #[inline]
+    pub fn backward(&self) {
+        let shape = self.shape();
+        self.backward_with(ArrayD::ones(shape));
+    }
+
+    /// Computes the vector-Jacobian product of the given gradient with respect to every leaf that
+    /// requires it, accumulating the result in their [`Tensor::grad`].
+    pub fn backward_with(&self, gradient: ArrayD<F>) {
+        assert_eq!(
+            gradient.shape(),
+            self.shape(),
+            "Gradient must have the same shape as the tensor"
+        );
+        if !self.requires_grad() {
+            return;
+        }
+
+        let mut grads: HashMap<*const Inner<F>, ArrayD<F>> = HashMap::new();
+        grads.insert(self.id(), gradient);
+
+        for tensor in self.topological_order().into_iter().rev() {
+            let Some(grad) = grads.remove(&tensor.id()) else {
+                continue;
+            };
+
+            match &tensor.inner.operation {
+                Some(Operation { parents, backward }) => {
+                    let parent_grads = backward(&grad, &tensor.data(), parents);
+                    debug_assert_eq!(parents.len(), parent_grads.len());
+
+                    for (parent, parent_grad) in parents.iter().zip(parent_grads) {
+                        if !parent.requires_grad() {
+                            continue;
+                        }
+                        match grads.get_mut(&parent.id()) {
+                            Some(acc) => *acc += &parent_grad,
+                            None => {
+                                grads.insert(parent.id(), parent_grad);
+                            }
+                        }
+                    }
+                }
+                None => {
+                    let mut acc = tensor.inner.grad.borrow_mut();
+                    match acc.as_mut() {
+                        Some(acc) => *acc += &grad,
+                        None => *acc = Some(grad),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<F: Float> fmt::Debug for Tensor<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tensor")
+            .field("data", &*self.data())
+            .field("grad", &*self.inner.grad.borrow())
+            .field("requires_grad", &self.requires_grad())
+            .finish()
+    }
+}
+
+impl<F: Float, D: Dimension> From<Array<F, D>> for Tensor<F> {
+    #[inline]
+    fn from(data: Array<F, D>) -> Self {
+        Self::new(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+
+    #[test]
+    fn constant_has_no_grad() {
+        let a = Tensor::new(array![[1.0, 2.0]]);
+        let b = Tensor::new(array![[3.0, 4.0]]);
+        let c = &a * &b;
+        c.backward();
+
+        assert!(!c.requires_grad());
+        assert!(a.grad().is_none());
+        assert!(b.grad().is_none());
+    }
+
+    #[test]
+    fn shared_node_accumulates() {
+        // y = x * x + x -> dy/dx = 2x + 1
+        let x = Tensor::new_requires_grad(array![[1.0, -2.0], [3.0, 0.5]]);
+        let y = &(&x * &x) + &x;
+        y.backward();
+
+        assert_array_eq!(
+            x.grad().unwrap(),
+            array![[3.0, -3.0], [7.0, 2.0]].into_dyn()
+        );
+    }
+
+    #[test]
+    fn backward_accumulates_between_calls() {
+        let x = Tensor::new_requires_grad(array![[1.0, 2.0]]);
+        (&x * 2.0).backward();
+        (&x * 2.0).backward();
+        assert_array_eq!(x.grad().unwrap(), array![[4.0, 4.0]].into_dyn());
+
+        x.zero_grad();
+        assert_array_eq!(x.grad().unwrap(), array![[0.0, 0.0]].into_dyn());
+    }
+
+    #[test]
+    fn parts_mut_requires_unique_reference() {
+        let mut x = Tensor::new_requires_grad(array![[1.0, 2.0]]);
+        let y = x.relu();
+        assert!(x.parts_mut().is_none());
+
+        drop(y);
+        let (data, grad) = x.parts_mut().unwrap();
+        assert_array_eq!(data, array![[1.0, 2.0]].into_dyn());
+        assert_array_eq!(grad, array![[0.0, 0.0]].into_dyn());
+    }
+}
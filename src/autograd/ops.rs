@@ -0,0 +1,370 @@
+use super::Tensor;
+use crate::module::activation::softmax::{log_softmax, softmax};
+use crate::Float;
+use ndarray::prelude::*;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Reduces a broadcasted gradient back to the shape of the operand.
+#[inline]
+fn unbroadcast<F: Float>(mut grad: ArrayD<F>, shape: &[usize]) -> ArrayD<F> {
+    while grad.ndim() > shape.len() {
+        grad = grad.sum_axis(Axis(0));
+    }
+    for (i, &size) in shape.iter().enumerate() {
+        if size == 1 && grad.shape()[i] != 1 {
+            grad = grad.sum_axis(Axis(i)).insert_axis(Axis(i));
+        }
+    }
+    grad
+}
+
+#[inline]
+fn as_matrix<F>(array: &ArrayD<F>) -> ArrayView2<'_, F> {
+    array
+        .view()
+        .into_dimensionality()
+        .expect("Matrix product is only defined for 2-D tensors")
+}
+
+impl<F: Float> Tensor<F> {
+    /// Matrix product of 2-D tensors, `(n, m) x (m, k) -> (n, k)`.
+    #[inline]
+    #[must_use]
+    pub fn matmul(&self, other: &Tensor<F>) -> Tensor<F> {
+        let data = as_matrix(&self.data()).dot(&as_matrix(&other.data()));
+        Tensor::from_operation(
+            data.into_dyn(),
+            vec![self.clone(), other.clone()],
+            |grad, _, parents| {
+                let grad = as_matrix(grad);
+                vec![
+                    grad.dot(&as_matrix(&parents[1].data()).t()).into_dyn(),
+                    as_matrix(&parents[0].data()).t().dot(&grad).into_dyn(),
+                ]
+            },
+        )
+    }
+
+    /// Reshapes the tensor, keeping the number of elements.
+    #[inline]
+    #[must_use]
+    pub fn reshape(&self, shape: &[usize]) -> Tensor<F> {
+        let data = self
+            .data()
+            .as_standard_layout()
+            .into_shape(shape)
+            .expect("Reshape must keep the number of elements")
+            .into_owned();
+        Tensor::from_operation(data, vec![self.clone()], |grad, _, parents| {
+            let shape = parents[0].shape();
+            vec![grad
+                .as_standard_layout()
+                .into_shape(shape)
+                .unwrap()
+                .into_owned()]
+        })
+    }
+
+    /// Reverses the order of the axes.
+    #[inline]
+    #[must_use]
+    pub fn t(&self) -> Tensor<F> {
+        let data = self.data().t().to_owned();
+        Tensor::from_operation(data, vec![self.clone()], |grad, _, _| {
+            vec![grad.t().to_owned()]
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn relu(&self) -> Tensor<F> {
+        let data = self.data().mapv(|x| x.max(F::zero()));
+        Tensor::from_operation(data, vec![self.clone()], |grad, _, parents| {
+            let mask = parents[0]
+                .data()
+                .mapv(|x| if x > F::zero() { F::one() } else { F::zero() });
+            vec![grad * &mask]
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn exp(&self) -> Tensor<F> {
+        let data = self.data().mapv(F::exp);
+        Tensor::from_operation(data, vec![self.clone()], |grad, output, _| {
+            vec![grad * output]
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn ln(&self) -> Tensor<F> {
+        let data = self.data().mapv(F::ln);
+        Tensor::from_operation(data, vec![self.clone()], |grad, _, parents| {
+            vec![grad / &*parents[0].data()]
+        })
+    }
+
+    /// Sum of all the elements, as a 0-dimensional tensor.
+    #[inline]
+    #[must_use]
+    pub fn sum(&self) -> Tensor<F> {
+        let data = arr0(self.data().sum()).into_dyn();
+        Tensor::from_operation(data, vec![self.clone()], |grad, _, parents| {
+            vec![ArrayD::from_elem(parents[0].shape(), grad.sum())]
+        })
+    }
+
+    /// Mean of all the elements, as a 0-dimensional tensor.
+    #[inline]
+    #[must_use]
+    pub fn mean(&self) -> Tensor<F> {
+        let n = F::from_usize(self.data().len());
+        &self.sum() / n
+    }
+
+    /// Sum along the given axis, keeping it with length one.
+    #[inline]
+    #[must_use]
+    pub fn sum_axis(&self, axis: Axis) -> Tensor<F> {
+        let data = self.data().sum_axis(axis).insert_axis(axis);
+        Tensor::from_operation(data, vec![self.clone()], |grad, _, parents| {
+            vec![grad.broadcast(parents[0].shape()).unwrap().to_owned()]
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn softmax(&self, axis: Axis) -> Tensor<F> {
+        let data = softmax(self.data().clone(), axis);
+        Tensor::from_operation(data, vec![self.clone()], move |grad, output, _| {
+            // Jacobian-vector product of every slice: s * (g - sum(g * s))
+            let dot = (grad * output).sum_axis(axis).insert_axis(axis);
+            vec![output * &(grad - &dot)]
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn log_softmax(&self, axis: Axis) -> Tensor<F> {
+        let data = log_softmax(self.data().clone(), axis);
+        Tensor::from_operation(data, vec![self.clone()], move |grad, output, _| {
+            let sum = grad.sum_axis(axis).insert_axis(axis);
+            vec![grad - &(output.mapv(F::exp) * sum)]
+        })
+    }
+}
+
+macro_rules! binary_op {
+    ($trait:ident, $method:ident, $op:tt, $backward:expr) => {
+        impl<F: Float> $trait<&Tensor<F>> for &Tensor<F> {
+            type Output = Tensor<F>;
+
+            #[inline]
+            fn $method(self, other: &Tensor<F>) -> Tensor<F> {
+                let data = &*self.data() $op &*other.data();
+                Tensor::from_operation(
+                    data,
+                    vec![self.clone(), other.clone()],
+                    |grad, _, parents| {
+                        let (a, b) = (parents[0].data(), parents[1].data());
+                        let (grad_a, grad_b) = $backward(grad, &a, &b);
+                        vec![unbroadcast(grad_a, a.shape()), unbroadcast(grad_b, b.shape())]
+                    },
+                )
+            }
+        }
+    };
+}
+
+type Gradients<F> = (ArrayD<F>, ArrayD<F>);
+
+#[inline]
+fn add_backward<F: Float>(grad: &ArrayD<F>, _: &ArrayD<F>, _: &ArrayD<F>) -> Gradients<F> {
+    (grad.clone(), grad.clone())
+}
+
+#[inline]
+fn sub_backward<F: Float>(grad: &ArrayD<F>, _: &ArrayD<F>, _: &ArrayD<F>) -> Gradients<F> {
+    (grad.clone(), grad.mapv(|x| -x))
+}
+
+#[inline]
+fn mul_backward<F: Float>(grad: &ArrayD<F>, a: &ArrayD<F>, b: &ArrayD<F>) -> Gradients<F> {
+    (grad * b, grad * a)
+}
+
+#[inline]
+fn div_backward<F: Float>(grad: &ArrayD<F>, a: &ArrayD<F>, b: &ArrayD<F>) -> Gradients<F> {
+    (grad / b, -(grad * a) / (b * b))
+}
+
+binary_op!(Add, add, +, add_backward);
+binary_op!(Sub, sub, -, sub_backward);
+binary_op!(Mul, mul, *, mul_backward);
+binary_op!(Div, div, /, div_backward);
+
+macro_rules! scalar_op {
+    ($trait:ident, $method:ident, $op:tt, $backward:expr) => {
+        impl<F: Float> $trait<F> for &Tensor<F> {
+            type Output = Tensor<F>;
+
+            #[inline]
+            fn $method(self, other: F) -> Tensor<F> {
+                let data = &*self.data() $op other;
+                Tensor::from_operation(data, vec![self.clone()], move |grad, _, _| {
+                    vec![$backward(grad, other)]
+                })
+            }
+        }
+    };
+}
+
+scalar_op!(Add, add, +, |g: &ArrayD<F>, _| g.clone());
+scalar_op!(Sub, sub, -, |g: &ArrayD<F>, _| g.clone());
+scalar_op!(Mul, mul, *, |g: &ArrayD<F>, s| g * s);
+scalar_op!(Div, div, /, |g: &ArrayD<F>, s| g / s);
+
+impl<F: Float> Neg for &Tensor<F> {
+    type Output = Tensor<F>;
+
+    #[inline]
+    fn neg(self) -> Tensor<F> {
+        self * -F::one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+
+    #[test]
+    fn broadcast_add() {
+        let a = Tensor::new_requires_grad(array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        let b = Tensor::new_requires_grad(array![[10.0, 20.0]]);
+        let c = &a + &b;
+        c.backward();
+
+        assert_array_eq!(*c.data(), array![[11.0, 22.0], [13.0, 24.0], [15.0, 26.0]]);
+        assert_array_eq!(a.grad().unwrap(), Array2::<f64>::ones((3, 2)));
+        assert_array_eq!(b.grad().unwrap(), array![[3.0, 3.0]]);
+    }
+
+    #[test]
+    fn div() {
+        let a = Tensor::new_requires_grad(array![[1.0, 2.0]]);
+        let b = Tensor::new_requires_grad(array![[4.0, -2.0]]);
+        (&a / &b).backward();
+
+        assert_array_eq!(a.grad().unwrap(), array![[0.25, -0.5]]);
+        assert_array_eq!(b.grad().unwrap(), array![[-0.0625, -0.5]]);
+    }
+
+    #[test]
+    fn matmul() {
+        let a = Tensor::new_requires_grad(array![[1.0, 2.0], [3.0, 4.0]]);
+        let b = Tensor::new_requires_grad(array![[1.0], [-1.0]]);
+        let c = a.matmul(&b);
+        c.backward();
+
+        assert_array_eq!(*c.data(), array![[-1.0], [-1.0]]);
+        assert_array_eq!(a.grad().unwrap(), array![[1.0, -1.0], [1.0, -1.0]]);
+        assert_array_eq!(b.grad().unwrap(), array![[4.0], [6.0]]);
+    }
+
+    #[test]
+    fn reshape() {
+        let a = Tensor::new_requires_grad(array![[[1.0, 2.0], [3.0, 4.0]]]);
+        let w = Tensor::new(array![[1.0, 2.0, 3.0, 4.0]]);
+        let b = &a.reshape(&[1, 4]) * &w;
+        b.backward();
+
+        assert_eq!(vec![1, 4], b.shape());
+        assert_array_eq!(a.grad().unwrap(), array![[[1.0, 2.0], [3.0, 4.0]]]);
+    }
+
+    #[test]
+    fn transpose() {
+        let a = Tensor::new_requires_grad(array![[1.0, 2.0, 3.0]]);
+        let b = Tensor::new(array![[1.0, 2.0, 3.0]]);
+        (&a.t() * &b.t()).backward();
+
+        assert_array_eq!(a.grad().unwrap(), array![[1.0, 2.0, 3.0]]);
+    }
+
+    #[test]
+    fn exp_ln() {
+        let a = Tensor::new_requires_grad(array![[1.0, 2.0]]);
+        let b = a.exp().ln();
+        b.backward();
+
+        assert_array_eq!(*b.data(), array![[1.0, 2.0]]);
+        assert_array_eq!(a.grad().unwrap(), array![[1.0, 1.0]]);
+    }
+
+    #[test]
+    fn mean() {
+        let a = Tensor::new_requires_grad(array![[1.0, 2.0], [3.0, 4.0]]);
+        let b = a.mean();
+        b.backward();
+
+        assert_array_eq!(*b.data(), arr0(2.5));
+        assert_array_eq!(a.grad().unwrap(), Array2::from_elem((2, 2), 0.25));
+    }
+
+    #[test]
+    fn sum_axis() {
+        let a = Tensor::new_requires_grad(array![[1.0, 2.0], [3.0, 4.0]]);
+        let w = Tensor::new(array![[1.0], [2.0]]);
+        let b = &a.sum_axis(Axis(1)) * &w;
+        b.backward();
+
+        assert_array_eq!(*b.data(), array![[3.0], [14.0]]);
+        assert_array_eq!(a.grad().unwrap(), array![[1.0, 1.0], [2.0, 2.0]]);
+    }
+
+    #[test]
+    fn softmax_gradient_sums_to_zero() {
+        let a = Tensor::new_requires_grad(array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        let b = a.softmax(Axis(1));
+        b.backward();
+
+        assert_array_eq!(b.data().sum_axis(Axis(1)), array![1.0, 1.0, 1.0]);
+        assert_array_eq!(a.grad().unwrap(), Array2::<f64>::zeros((3, 2)));
+    }
+
+    #[test]
+    fn log_softmax_matches_softmax() {
+        let input = array![[1.0, 2.0, 3.0], [1000.0, 0.0, -1000.0]];
+        let weight = Tensor::new(array![[1.0, 0.0, 2.0], [0.5, 1.0, 0.0]]);
+
+        let a = Tensor::new_requires_grad(input.clone());
+        let log = a.log_softmax(Axis(1));
+        (&log * &weight).backward();
+
+        let b = Tensor::new_requires_grad(input);
+        let expected = b.softmax(Axis(1)).ln();
+        (&expected * &weight).backward();
+
+        assert!(log.data().iter().all(|x: &f64| x.is_finite()));
+        assert_array_eq!(log.data().mapv(f64::exp), expected.data().mapv(f64::exp));
+        assert_array_eq!(
+            a.grad().unwrap().index_axis(Axis(0), 0),
+            b.grad().unwrap().index_axis(Axis(0), 0)
+        );
+    }
+
+    #[test]
+    fn cross_entropy() {
+        // Gradient of the cross entropy with respect to the logits is softmax(x) - y
+        let x = Tensor::new_requires_grad(array![[1.0, 2.0, 3.0]]);
+        let y = Tensor::new(array![[0.0, 1.0, 0.0]]);
+        let loss = -&(&y * &x.log_softmax(Axis(1))).sum();
+        loss.backward();
+
+        let expected =
+            softmax(array![[1.0, 2.0, 3.0]].into_dyn(), Axis(1)) - array![[0.0, 1.0, 0.0]];
+        assert_array_eq!(x.grad().unwrap(), expected);
+    }
+}
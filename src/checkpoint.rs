@@ -0,0 +1,384 @@
+//! Checkpoints to resume an interrupted training run exactly where it stopped: the parameters of
+//! the model, the state of the optimizer and of the learning rate scheduler, and the position of
+//! the [`DataLoader`](crate::data::DataLoader), including the randomness of its shuffling and
+//! sampling.
+//!
+//! A resumed run gives bit-identical results to an uninterrupted one as long as every component
+//! is created as in the interrupted run, with seeded random number generators, before the
+//! checkpoint is loaded into it.
+//!
+//! ```
+//! use rstorch::checkpoint::Checkpoint;
+//! use rstorch::optim::lr_scheduler::StepLR;
+//! use rstorch::prelude::*;
+//! use rstorch::{Linear, SGD};
+//!
+//! let mut model = Linear::new(4, 2);
+//! let mut optim = SGD::new(0.1).momentum(0.9);
+//! let mut scheduler = StepLR::new(&optim, 10, 0.5);
+//! // ... train for a while
+//! let checkpoint = Checkpoint::new(model.state_dict(), optim.state()).scheduler(scheduler.state());
+//! let mut file = Vec::new(); // or `checkpoint.save(path)`
+//! checkpoint.write_to(&mut file)?;
+//!
+//! // The resumed run creates everything as before, then restores it
+//! let checkpoint = Checkpoint::read_from(file.as_slice())?;
+//! model.load_state_dict(&checkpoint.model)?;
+//! optim.load_state(&mut model, &checkpoint.optimizer)?;
+//! scheduler.load_state(&checkpoint.scheduler)?;
+//! # Ok::<(), rstorch::Error>(())
+//! ```
+use crate::data::DataLoaderState;
+use crate::module::state_dict::{read_bytes, read_exact, read_len, write_len};
+use crate::{Error, Float, Result, StateDict};
+use ndarray::prelude::*;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"RSTCKPT\0";
+const VERSION: u32 = 1;
+
+/// Arrays and counters of an optimizer or a learning rate scheduler, see
+/// [`Optimizer::state`](crate::optim::Optimizer::state).
+#[derive(Debug, Clone, PartialEq)]
+pub struct State<F = f64> {
+    pub arrays: StateDict<F>,
+    pub counters: BTreeMap<String, u64>,
+}
+
+impl<F> Default for State<F> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            arrays: StateDict::default(),
+            counters: BTreeMap::new(),
+        }
+    }
+}
+
+#[inline]
+fn missing(name: &str) -> Error {
+    Error::StateDictKeys {
+        missing: vec![name.to_string()],
+        unexpected: vec![],
+    }
+}
+
+impl<F: Float> State<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn array(&self, name: &str) -> Result<&ArrayD<F>> {
+        self.arrays.get(name).ok_or_else(|| missing(name))
+    }
+
+    /// Value of a 0-dimensional array.
+    #[inline]
+    pub fn scalar(&self, name: &str) -> Result<F> {
+        let array = self.array(name)?;
+        match array.ndim() {
+            0 => Ok(*array.first().unwrap()),
+            _ => Err(Error::ShapeMismatch {
+                name: name.to_string(),
+                expected: vec![],
+                found: array.shape().to_vec(),
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn counter(&self, name: &str) -> Result<u64> {
+        self.counters
+            .get(name)
+            .copied()
+            .ok_or_else(|| missing(name))
+    }
+
+    /// Fails with every array or counter whose name is not `expected`.
+    pub(crate) fn check_keys(&self, expected: impl Fn(&str) -> bool) -> Result<()> {
+        let unexpected: Vec<_> = self
+            .arrays
+            .keys()
+            .chain(self.counters.keys().map(String::as_str))
+            .filter(|&name| !expected(name))
+            .map(str::to_string)
+            .collect();
+        match unexpected.is_empty() {
+            true => Ok(()),
+            false => Err(Error::StateDictKeys {
+                missing: vec![],
+                unexpected,
+            }),
+        }
+    }
+}
+
+/// Everything needed to resume training, see the [module](self) documentation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint<F = f64> {
+    pub model: StateDict<F>,
+    pub optimizer: State<F>,
+    /// Empty without a scheduler
+    pub scheduler: State<F>,
+    pub data_loader: DataLoaderState,
+}
+
+impl<F: Float> Checkpoint<F> {
+    #[inline]
+    #[must_use]
+    pub fn new(model: StateDict<F>, optimizer: State<F>) -> Self {
+        Self {
+            model,
+            optimizer,
+            scheduler: State::new(),
+            data_loader: DataLoaderState::default(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn scheduler(mut self, scheduler: State<F>) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn data_loader(mut self, data_loader: DataLoaderState) -> Self {
+        self.data_loader = data_loader;
+        self
+    }
+
+    /// Written as the magic `RSTCKPT\0` and a `u32` version, followed by every array in the
+    /// format of [`StateDict`] and every counter as its name and a `u64`, with the names
+    /// prefixed by `model.`, `optimizer.`, `scheduler.` or `data_loader.`.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<()> {
+        let prefixed = |prefix: &str, arrays: &StateDict<F>| {
+            arrays
+                .iter()
+                .map(|(name, array)| (format!("{prefix}.{name}"), array.clone()))
+                .collect::<Vec<_>>()
+        };
+        let arrays: StateDict<F> = prefixed("model", &self.model)
+            .into_iter()
+            .chain(prefixed("optimizer", &self.optimizer.arrays))
+            .chain(prefixed("scheduler", &self.scheduler.arrays))
+            .collect();
+
+        let DataLoaderState { epoch, batch, seed } = self.data_loader;
+        let counters: Vec<_> = [
+            ("optimizer", &self.optimizer),
+            ("scheduler", &self.scheduler),
+        ]
+        .into_iter()
+        .flat_map(|(prefix, state)| {
+            state
+                .counters
+                .iter()
+                .map(move |(name, &value)| (format!("{prefix}.{name}"), value))
+        })
+        .chain([
+            ("data_loader.epoch".to_string(), epoch as u64),
+            ("data_loader.batch".to_string(), batch as u64),
+            ("data_loader.seed".to_string(), seed),
+        ])
+        .collect();
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        arrays.write_to(&mut writer)?;
+        write_len(&mut writer, counters.len())?;
+        for (name, value) in counters {
+            write_len(&mut writer, name.len())?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self> {
+        let mut magic = [0; 8];
+        read_exact(&mut reader, &mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::Format("not a checkpoint file".to_string()));
+        }
+        let mut version = [0; 4];
+        read_exact(&mut reader, &mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(Error::Format(format!("unsupported version {version}")));
+        }
+
+        let mut checkpoint = Self::new(StateDict::new(), State::new());
+        for (name, array) in StateDict::read_from(&mut reader)? {
+            let (section, name) = name.split_once('.').unwrap_or(("", &name));
+            let arrays = match section {
+                "model" => &mut checkpoint.model,
+                "optimizer" => &mut checkpoint.optimizer.arrays,
+                "scheduler" => &mut checkpoint.scheduler.arrays,
+                _ => return Err(Error::Format(format!("unexpected array {section}.{name}"))),
+            };
+            arrays.insert(name, array);
+        }
+
+        for _ in 0..read_len(&mut reader)? {
+            let len = read_len(&mut reader)?;
+            let name = String::from_utf8(read_bytes(&mut reader, len)?)
+                .map_err(|_| Error::Format("name is not valid UTF-8".to_string()))?;
+            let mut value = [0; 8];
+            read_exact(&mut reader, &mut value)?;
+            let value = u64::from_le_bytes(value);
+
+            let data_loader = &mut checkpoint.data_loader;
+            match name.split_once('.').unwrap_or(("", &name)) {
+                ("optimizer", counter) => {
+                    checkpoint.optimizer.counters.insert(counter.into(), value);
+                }
+                ("scheduler", counter) => {
+                    checkpoint.scheduler.counters.insert(counter.into(), value);
+                }
+                ("data_loader", "epoch") => data_loader.epoch = value as usize,
+                ("data_loader", "batch") => data_loader.batch = value as usize,
+                ("data_loader", "seed") => data_loader.seed = value,
+                _ => return Err(Error::Format(format!("unexpected counter {name}"))),
+            }
+        }
+        Ok(checkpoint)
+    }
+
+    #[inline]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    #[inline]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Basic, DataLoader, RandomSampler};
+    use crate::optim::lr_scheduler::StepLR;
+    use crate::prelude::*;
+    use crate::{Adam, Linear, ReLU, Sequential};
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    type Loader = DataLoader<Basic<(Array1<f64>, Array1<f64>)>, RandomSampler<SmallRng>>;
+
+    struct Run {
+        model: Sequential,
+        optim: Adam,
+        scheduler: StepLR,
+        loader: Loader,
+    }
+
+    fn run() -> Run {
+        let inputs = (0..40).map(|i| array![i as f64 / 40.0, (i % 7) as f64 / 7.0]);
+        let targets = (0..40).map(|i| array![(i % 3) as f64]);
+        let data = Basic::from_iter_with_targets(inputs, targets);
+        let sampler = RandomSampler::new(40, false, 40, SmallRng::seed_from_u64(3));
+        let optim = Adam::new(0.01);
+        Run {
+            model: sequential!(Linear(2, 8), ReLU(), Linear(8, 1)),
+            scheduler: StepLR::new(&optim, 1, 0.5),
+            optim,
+            loader: DataLoader::new(data, 4, true, sampler).seed(7),
+        }
+    }
+
+    /// Trains until the end of the third epoch, or until `interrupt` batches were trained.
+    fn train(run: &mut Run, interrupt: Option<usize>) -> Option<Checkpoint> {
+        let mut batches = 0;
+        let first = run.loader.state().epoch.saturating_sub(1);
+        for _ in first..3 {
+            let mut iter = run.loader.iter_array();
+            while let Some((input, target)) = iter.next().transpose().unwrap() {
+                run.optim.zero_grad(&mut run.model);
+                let output = run.model.forward(input.into_dyn());
+                run.model.backward(output - target.into_dyn()).unwrap();
+                run.optim.step(&mut run.model);
+
+                batches += 1;
+                if Some(batches) == interrupt {
+                    let checkpoint = Checkpoint::new(run.model.state_dict(), run.optim.state())
+                        .scheduler(run.scheduler.state())
+                        .data_loader(iter.state());
+                    return Some(checkpoint);
+                }
+            }
+            run.scheduler.step(&mut run.optim);
+        }
+        None
+    }
+
+    #[test]
+    fn resume_is_exact() {
+        let mut uninterrupted = run();
+        let mut interrupted = run();
+        let initial = uninterrupted.model.state_dict();
+        interrupted.model.load_state_dict(&initial).unwrap();
+
+        train(&mut uninterrupted, None);
+        // In the middle of the second epoch
+        let checkpoint = train(&mut interrupted, Some(13)).unwrap();
+        assert_eq!(2, checkpoint.data_loader.epoch);
+        assert_eq!(3, checkpoint.data_loader.batch);
+
+        let mut file = Vec::new();
+        checkpoint.write_to(&mut file).unwrap();
+        let checkpoint = Checkpoint::read_from(file.as_slice()).unwrap();
+
+        let mut resumed = run();
+        resumed.model.load_state_dict(&checkpoint.model).unwrap();
+        resumed
+            .optim
+            .load_state(&mut resumed.model, &checkpoint.optimizer)
+            .unwrap();
+        resumed.scheduler.load_state(&checkpoint.scheduler).unwrap();
+        resumed.loader.load_state(checkpoint.data_loader);
+        train(&mut resumed, None);
+
+        assert_ne!(initial, uninterrupted.model.state_dict());
+        assert_eq!(uninterrupted.model.state_dict(), resumed.model.state_dict());
+        assert_eq!(uninterrupted.optim.state(), resumed.optim.state());
+        assert_eq!(uninterrupted.loader.state(), resumed.loader.state());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut optimizer = State::new();
+        optimizer
+            .arrays
+            .insert("param_groups.0.lr", arr0(0.1).into_dyn());
+        optimizer
+            .counters
+            .insert("state.0.weight.step".to_string(), 3);
+        let mut model = StateDict::new();
+        model.insert("0.weight", array![[1.0, 2.0]].into_dyn());
+        let data_loader = DataLoaderState {
+            epoch: 2,
+            batch: 5,
+            seed: u64::MAX,
+        };
+        let checkpoint = Checkpoint::new(model, optimizer).data_loader(data_loader);
+
+        let mut file = Vec::new();
+        checkpoint.write_to(&mut file).unwrap();
+        assert_eq!(checkpoint, Checkpoint::read_from(file.as_slice()).unwrap());
+        assert!(matches!(
+            Checkpoint::<f64>::read_from(&file[..file.len() - 1]),
+            Err(Error::Format(_))
+        ));
+    }
+}
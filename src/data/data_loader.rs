@@ -2,7 +2,10 @@ use std::iter::FusedIterator;
 
 use super::dataset::Shuffler;
 use super::{dataset::Dataset, sampler::Sampler};
+use crate::{Error, Result};
 use ndarray::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 #[derive(Debug)]
 enum Shuffle<D> {
@@ -30,16 +33,34 @@ impl<D: Dataset> Dataset for Shuffle<D> {
     }
 }
 
+/// Position of a [`DataLoader`] in the training run, to resume it exactly where it stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DataLoaderState {
+    /// Number of epochs started
+    pub epoch: usize,
+    /// Number of batches yielded by the current epoch
+    pub batch: usize,
+    /// Seed of the shuffling
+    pub seed: u64,
+}
+
 pub struct DataLoader<D, S> {
     dataset: Shuffle<D>,
     batch_size: usize,
     sampler: S,
+    seed: u64,
+    epoch: usize,
+    batch: usize,
+    /// Whether the next epoch continues the one of a loaded state
+    resume: bool,
 }
 
 impl<D: Dataset, S: Sampler> DataLoader<D, S> {
+    /// The dataset is shuffled at every epoch with a random seed, see [`DataLoader::seed`].
     #[inline]
     #[must_use]
     pub fn new(dataset: D, batch_size: usize, shuffle: bool, sampler: S) -> Self {
+        assert!(batch_size > 0, "Batch size must be positive");
         let dataset = match shuffle {
             true => Shuffle::Yes(Shuffler::new(dataset)),
             false => Shuffle::No(dataset),
@@ -49,19 +70,33 @@ impl<D: Dataset, S: Sampler> DataLoader<D, S> {
             dataset,
             batch_size,
             sampler,
+            seed: rand::random(),
+            epoch: 0,
+            batch: 0,
+            resume: false,
         }
     }
 
+    /// Seeds the shuffling, whose permutation of every epoch only depends on the seed and the
+    /// epoch.
+    #[inline]
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Draws a new random seed, so the following epochs use fresh permutations instead of those
+    /// given by the previous seed. The dataset is already shuffled at every epoch.
     #[inline]
     pub fn shuffle(&mut self) {
-        if let Shuffle::Yes(data) = &mut self.dataset {
-            data.shuffle();
-        }
+        self.seed = rand::random();
     }
 
+    /// Starts the next epoch, or continues the one of the state given to
+    /// [`DataLoader::load_state`].
     #[inline]
     pub fn iter(&mut self) -> DataLoaderIter<'_, D, S> {
-        self.shuffle();
         DataLoaderIter::new(self)
     }
 
@@ -75,6 +110,50 @@ impl<D: Dataset, S: Sampler> DataLoader<D, S> {
         // Currently it ignores the remaining elements
         self.dataset.len() < self.batch_size
     }
+
+    #[inline]
+    pub fn state(&self) -> DataLoaderState {
+        DataLoaderState {
+            epoch: self.epoch,
+            batch: self.batch,
+            seed: self.seed,
+        }
+    }
+
+    /// Makes the next iteration continue after the last batch yielded before `state` was taken.
+    /// The sampler must be created as in the interrupted run.
+    #[inline]
+    pub fn load_state(&mut self, state: DataLoaderState) {
+        self.epoch = state.epoch;
+        self.batch = state.batch;
+        self.seed = state.seed;
+        self.resume = state.epoch > 0;
+    }
+
+    /// Shuffles the dataset for the epoch being started and skips the batches already yielded.
+    fn start_epoch(&mut self) -> S::Iter {
+        // A finished epoch is resumed at the next one
+        let restored = std::mem::take(&mut self.resume);
+        if !restored || self.batch >= self.len() {
+            self.epoch += 1;
+            self.batch = 0;
+        }
+        if restored {
+            self.sampler.set_epoch(self.epoch - 1);
+        }
+
+        if let Shuffle::Yes(data) = &mut self.dataset {
+            let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+            rng.set_stream((self.epoch - 1) as u64);
+            data.shuffle(&mut rng);
+        }
+
+        let mut iter = self.sampler.iter();
+        for _ in 0..self.batch * self.batch_size {
+            iter.next();
+        }
+        iter
+    }
 }
 
 impl<D, T1, D1, T2, D2, S> DataLoader<D, S>
@@ -86,26 +165,36 @@ where
     T1: Clone,
     T2: Clone,
 {
+    /// Like [`DataLoader::iter`], stacking the samples and targets of every batch. A batch fails
+    /// with [`Error::ShapeMismatch`] if its samples, or its targets, have different shapes.
     #[inline]
     pub fn iter_array(&mut self) -> ArrayDataLoaderIter<'_, D, S> {
-        self.shuffle();
         ArrayDataLoaderIter::new(self)
     }
 }
 
 // -- GENERIC ITER --
 pub struct DataLoaderIter<'a, D, S: Sampler> {
-    data_loader: &'a DataLoader<D, S>,
+    data_loader: &'a mut DataLoader<D, S>,
     iter: S::Iter,
 }
 
-impl<'a, D, S: Sampler> DataLoaderIter<'a, D, S> {
+impl<'a, D: Dataset, S: Sampler> DataLoaderIter<'a, D, S> {
     #[inline]
     #[must_use]
     pub(crate) fn new(data_loader: &'a mut DataLoader<D, S>) -> Self {
-        let iter = data_loader.sampler.iter();
+        let iter = data_loader.start_epoch();
         Self { data_loader, iter }
     }
+
+    /// State of the data loader after the last batch yielded.
+    #[inline]
+    pub fn state(&self) -> DataLoaderState {
+        let DataLoader {
+            epoch, batch, seed, ..
+        } = *self.data_loader;
+        DataLoaderState { epoch, batch, seed }
+    }
 }
 
 impl<'a, D: Dataset, S: Sampler> Iterator for DataLoaderIter<'a, D, S> {
@@ -113,9 +202,11 @@ impl<'a, D: Dataset, S: Sampler> Iterator for DataLoaderIter<'a, D, S> {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        (0..self.data_loader.batch_size)
+        let batch: Vec<_> = (0..self.data_loader.batch_size)
             .map(|_| self.data_loader.dataset.get(self.iter.next()?))
-            .collect()
+            .collect::<Option<_>>()?;
+        self.data_loader.batch += 1;
+        Some(batch)
     }
 
     #[inline]
@@ -164,17 +255,77 @@ where
 }
 
 // -- ARRAY ITER --
+/// Stacked samples and targets of a batch.
+type ArrayBatch<T1, D1, T2, D2> = (
+    Array<T1, <D1 as Dimension>::Larger>,
+    Array<T2, <D2 as Dimension>::Larger>,
+);
+
+/// Stacks the samples and targets at `indices` of the dataset, failing if their shapes differ.
+fn stack_batch<D, T1, D1, T2, D2>(
+    dataset: &D,
+    indices: &[usize],
+) -> Option<Result<ArrayBatch<T1, D1, T2, D2>>>
+where
+    D: Dataset<Item = (Array<T1, D1>, Array<T2, D2>)>,
+    D1: Dimension,
+    D2: Dimension,
+    T1: Clone,
+    T2: Clone,
+{
+    let (samples, targets): (Vec<_>, Vec<_>) = indices
+        .iter()
+        .map(|&index| dataset.get(index))
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    Some(
+        stack(&samples, indices, "sample")
+            .and_then(|samples| Ok((samples, stack(&targets, indices, "target")?))),
+    )
+}
+
+fn stack<T: Clone, D: Dimension>(
+    arrays: &[Array<T, D>],
+    indices: &[usize],
+    name: &str,
+) -> Result<Array<T, D::Larger>> {
+    let expected = arrays[0].shape();
+    if let Some((array, index)) = arrays
+        .iter()
+        .zip(indices)
+        .find(|(array, _)| array.shape() != expected)
+    {
+        return Err(Error::ShapeMismatch {
+            name: format!("{name} {index}"),
+            expected: expected.to_vec(),
+            found: array.shape().to_vec(),
+        });
+    }
+    let views: Vec<_> = arrays.iter().map(Array::view).collect();
+    Ok(ndarray::stack(Axis(0), &views).unwrap())
+}
+
 pub struct ArrayDataLoaderIter<'a, D, S: Sampler> {
-    data_loader: &'a DataLoader<D, S>,
+    data_loader: &'a mut DataLoader<D, S>,
     iter: S::Iter,
 }
 
-impl<'a, D, S: Sampler> ArrayDataLoaderIter<'a, D, S> {
+impl<'a, D: Dataset, S: Sampler> ArrayDataLoaderIter<'a, D, S> {
     #[inline]
     pub(crate) fn new(data_loader: &'a mut DataLoader<D, S>) -> Self {
-        let iter = data_loader.sampler.iter();
+        let iter = data_loader.start_epoch();
         Self { data_loader, iter }
     }
+
+    /// State of the data loader after the last batch yielded.
+    #[inline]
+    pub fn state(&self) -> DataLoaderState {
+        let DataLoader {
+            epoch, batch, seed, ..
+        } = *self.data_loader;
+        DataLoaderState { epoch, batch, seed }
+    }
 }
 
 impl<'a, D, T1, D1, T2, D2, S> Iterator for ArrayDataLoaderIter<'a, D, S>
@@ -186,24 +337,16 @@ where
     T1: Clone,
     T2: Clone,
 {
-    type Item = (Array<T1, D1::Larger>, Array<T2, D2::Larger>);
+    type Item = Result<ArrayBatch<T1, D1, T2, D2>>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (sample, target) = self.data_loader.dataset.get(self.iter.next()?)?;
-        let mut samples = sample.insert_axis(Axis(0));
-        let mut targets = target.insert_axis(Axis(0));
-        for _ in 1..self.data_loader.batch_size {
-            let (sample, target) = self.data_loader.dataset.get(self.iter.next()?)?;
-
-            samples
-                .append(Axis(0), sample.insert_axis(Axis(0)).view())
-                .ok()?;
-            targets
-                .append(Axis(0), target.insert_axis(Axis(0)).view())
-                .ok()?;
-        }
-        Some((samples, targets))
+        let indices: Vec<_> = (0..self.data_loader.batch_size)
+            .map(|_| self.iter.next())
+            .collect::<Option<_>>()?;
+        let batch = stack_batch(&self.data_loader.dataset, &indices)?;
+        self.data_loader.batch += 1;
+        Some(batch)
     }
 }
 
@@ -219,20 +362,10 @@ where
 {
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
-        let (sample, target) = self.data_loader.dataset.get(self.iter.next_back()?)?;
-        let mut samples = sample.insert_axis(Axis(0));
-        let mut targets = target.insert_axis(Axis(0));
-        for _ in 1..self.data_loader.batch_size {
-            let (sample, target) = self.data_loader.dataset.get(self.iter.next_back()?)?;
-
-            samples
-                .append(Axis(0), sample.insert_axis(Axis(0)).view())
-                .ok()?;
-            targets
-                .append(Axis(0), target.insert_axis(Axis(0)).view())
-                .ok()?;
-        }
-        Some((samples, targets))
+        let indices: Vec<_> = (0..self.data_loader.batch_size)
+            .map(|_| self.iter.next_back())
+            .collect::<Option<_>>()?;
+        stack_batch(&self.data_loader.dataset, &indices)
     }
 }
 
@@ -263,8 +396,9 @@ where
 mod tests {
     use super::*;
     use crate::data::dataset::test::TestDataset;
-    use crate::data::SequentialSampler;
+    use crate::data::{RandomSampler, SequentialSampler};
     use ndarray::IntoDimension;
+    use rand::rngs::SmallRng;
 
     struct ArrayTestDataset {
         samples: Vec<Array2<f64>>,
@@ -318,8 +452,79 @@ mod tests {
 
         let mut data = DataLoader::new(data, 32, false, sampler);
 
-        let (sample, label) = data.iter_array().next().unwrap();
+        let (sample, label) = data.iter_array().next().unwrap().unwrap();
         assert!(fill_dataset(32).into_iter().eq(sample.outer_iter()));
         assert!(fill_dataset(32).into_iter().eq(label.outer_iter()));
     }
+
+    #[test]
+    fn iter_array_shape_mismatch() {
+        let mut samples = fill_dataset(8);
+        samples[5] = Array::zeros([4, 3]);
+        let targets = fill_dataset(8);
+        let data = ArrayTestDataset { samples, targets };
+        let sampler = SequentialSampler::new(data.len());
+
+        let mut data = DataLoader::new(data, 4, false, sampler);
+        let mut iter = data.iter_array();
+        assert!(iter.next().unwrap().is_ok());
+        match iter.next() {
+            Some(Err(Error::ShapeMismatch {
+                name,
+                expected,
+                found,
+            })) => {
+                assert_eq!("sample 5", name);
+                assert_eq!((vec![4, 4], vec![4, 3]), (expected, found));
+            }
+            _ => panic!("Expected a shape mismatch"),
+        }
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn resume() {
+        let loader = || {
+            let data = TestDataset::new(0..20);
+            let sampler = RandomSampler::new(20, false, 20, SmallRng::seed_from_u64(1));
+            DataLoader::new(data, 4, true, sampler).seed(2)
+        };
+        let mut data = loader();
+        let epochs: Vec<Vec<_>> = (0..2).map(|_| data.iter().collect()).collect();
+        assert_ne!(epochs[0], epochs[1]);
+
+        let mut resumed = loader();
+        resumed.load_state(DataLoaderState {
+            epoch: 1,
+            batch: 2,
+            seed: 2,
+        });
+        let mut iter = resumed.iter();
+        assert_eq!(epochs[0][2..3], [iter.next().unwrap()]);
+        assert_eq!(3, iter.state().batch);
+        assert_eq!(epochs[0][3..], iter.collect::<Vec<_>>());
+
+        // A finished epoch continues with the next one
+        assert_eq!(epochs[1], resumed.iter().collect::<Vec<_>>());
+        let mut resumed = loader();
+        resumed.load_state(data.state());
+        let mut data_next = data.iter();
+        assert_eq!(data_next.next(), resumed.iter().next());
+    }
+
+    #[test]
+    fn shuffle() {
+        let loader = || {
+            let data = TestDataset::new(0..20);
+            let sampler = SequentialSampler::new(data.len());
+            DataLoader::new(data, 4, true, sampler).seed(2)
+        };
+        let (mut data, mut shuffled) = (loader(), loader());
+        shuffled.shuffle();
+        assert_ne!(2, shuffled.state().seed);
+        assert_ne!(
+            data.iter().collect::<Vec<_>>(),
+            shuffled.iter().collect::<Vec<_>>()
+        );
+    }
 }
@@ -40,7 +40,6 @@ impl<T, T2> Basic<(T, T2)> {
 
 impl<T> FromIterator<T> for Basic<T> {
     #[inline]
-    #[must_use]
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         Self::new(Vec::from_iter(iter))
     }
@@ -2,11 +2,12 @@ use flate2::bufread::GzDecoder;
 use ndarray::{iter::AxisIter, prelude::*, Array, IntoDimension, RemoveAxis};
 use reqwest::{self, blocking::Response};
 use std::fs;
-use std::io::{copy, BufReader, Read};
+use std::io::{self, copy, BufReader, Read};
 use std::path::{Path, PathBuf};
 
 use crate::iterator::IteratorExt;
 use crate::prelude::*;
+use crate::{Error, Result};
 
 #[allow(clippy::upper_case_acronyms)]
 pub struct MNIST {
@@ -56,27 +57,41 @@ impl MNIST {
         "9 - nine",
     ];
 
+    /// Loads the train or test split from `root`, downloading the dataset first if `download`.
+    ///
+    /// # Panics
+    ///
+    /// If the dataset cannot be downloaded or loaded, see [`MNIST::try_new`].
     #[inline]
     #[must_use]
     pub fn new<P: AsRef<Path>>(root: P, train: bool, download: bool) -> Self {
+        Self::try_new(root, train, download).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if a file cannot be downloaded or has an unexpected checksum, or if the dataset is
+    /// missing or invalid.
+    pub fn try_new<P: AsRef<Path>>(root: P, train: bool, download: bool) -> Result<Self> {
         if download {
-            MNIST::download(&root);
+            MNIST::download(&root)?;
         }
 
-        assert!(
-            MNIST::check_exits(&root),
-            "Dataset not found. You can set download=true to download it"
-        );
+        if !MNIST::check_exits(&root) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Dataset not found. You can set download=true to download it",
+            )
+            .into());
+        }
 
-        let (data, labels) = MNIST::load_data(&root, train);
+        let (data, labels) = MNIST::load_data(&root, train)?;
         let mut path = PathBuf::new();
         path.push(&root);
-        MNIST {
+        Ok(MNIST {
             root: path,
             data,
             labels,
             train,
-        }
+        })
     }
 
     #[inline]
@@ -95,39 +110,26 @@ impl MNIST {
             .all(|r| r.is_some() && r.unwrap())
     }
 
-    fn download<P: AsRef<Path>>(root: P) {
+    fn download<P: AsRef<Path>>(root: P) -> Result<()> {
         // TODO: Download multiple files same time
-        // TODO: Check md5
-        // TODO: Improve exception handelning
         // TODO: FIND WHY FILENAME METHOD FAILS
-        // TODO: Add better error handling
         if MNIST::check_exits(&root) {
-            return;
+            return Ok(());
         }
 
-        match fs::create_dir_all(&root) {
-            Ok(_) => {}
-            Err(e) if matches!(e.kind(), std::io::ErrorKind::AlreadyExists) => {}
-            Err(e) => panic!("Failed to create root folder for dataset: {}", e),
-        }
+        fs::create_dir_all(&root)?;
 
-        for (filename, _md5) in Self::RESOURCES {
-            let mut data: Option<Response> = None;
-            for mirror in Self::MIRRORS {
-                let url = format!("{}{}", mirror, filename);
-                println!("Downloading {url}");
-
-                data = reqwest::blocking::get(url).ok();
-                data = match data.is_some() && data.as_ref().unwrap().status().is_success() {
-                    true => break,
-                    false => None,
-                }
-            }
+        for (filename, md5) in Self::RESOURCES {
+            let gz = Self::download_file(filename)?;
 
-            let gz: Vec<_> = match data {
-                None => panic!("Failed to download the file ({filename}) for any of the mirrors"),
-                Some(data) => data.bytes().unwrap().into_iter().collect(),
-            };
+            let digest = format!("{:x}", md5::compute(&gz));
+            if digest != md5 {
+                return Err(Error::Checksum {
+                    file: filename.to_owned(),
+                    expected: md5.to_owned(),
+                    found: digest,
+                });
+            }
 
             // Decode file
             let mut gz = GzDecoder::new(gz.as_slice());
@@ -155,26 +157,54 @@ impl MNIST {
             path.push(filename);
 
             // Store raw file
-            let mut file = fs::File::create(path).unwrap();
-            copy(&mut gz, &mut file).unwrap();
+            let mut file = fs::File::create(path)?;
+            copy(&mut gz, &mut file)?;
+        }
+        Ok(())
+    }
+
+    /// Bytes of the file from the first mirror that serves it.
+    fn download_file(filename: &str) -> Result<Vec<u8>> {
+        let mut errors = Vec::new();
+        for mirror in Self::MIRRORS {
+            let url = format!("{}{}", mirror, filename);
+            println!("Downloading {url}");
+
+            let response = reqwest::blocking::get(&url)
+                .and_then(Response::error_for_status)
+                .and_then(Response::bytes);
+            match response {
+                Ok(bytes) => return Ok(bytes.to_vec()),
+                Err(e) => errors.push(format!("{url} ({e})")),
+            }
         }
+        Err(Error::Download(format!(
+            "{filename} from any mirror: {}",
+            errors.join(", ")
+        )))
     }
 
     #[inline]
-    fn load_data<P: AsRef<Path>>(root: P, train: bool) -> (Array3<u8>, Array1<u8>) {
+    fn load_data<P: AsRef<Path>>(root: P, train: bool) -> Result<(Array3<u8>, Array1<u8>)> {
         let mut path = PathBuf::new();
         path.push(root);
 
         let start = if train { "train" } else { "t10k" };
         path.push(format!("{start}-images-idx3-ubyte"));
-        let data = read_image_file(&path);
+        let data = read_image_file(&path)?;
 
         path.pop();
         path.push(format!("{start}-labels-idx1-ubyte"));
-        let labels = read_label_file(&path);
-
-        assert_eq!(data.len_of(Axis(0)), labels.len_of(Axis(0)));
-        (data, labels)
+        let labels = read_label_file(&path)?;
+
+        if data.len_of(Axis(0)) != labels.len_of(Axis(0)) {
+            return Err(Error::ShapeMismatch {
+                name: format!("{start} labels"),
+                expected: vec![data.len_of(Axis(0))],
+                found: labels.shape().to_vec(),
+            });
+        }
+        Ok((data, labels))
     }
 }
 
@@ -265,33 +295,38 @@ magic!(f32, 13, 4);
 magic!(f64, 14, 8);
 
 #[inline]
-fn read_int<R: Read>(mut data: R) -> u32 {
+fn read_int<R: Read>(mut data: R) -> Result<u32> {
     let mut buff = [0; 4];
-    data.read_exact(&mut buff).unwrap();
-    u32::from_be_bytes(buff)
+    data.read_exact(&mut buff)?;
+    Ok(u32::from_be_bytes(buff))
 }
 
 fn read_sn3<T: MagicType<N_BYTES>, const N_BYTES: usize, const N_DIM: usize>(
     path: &Path,
-) -> Array<T, Dim<[usize; N_DIM]>>
+) -> Result<Array<T, Dim<[usize; N_DIM]>>>
 where
     [usize; N_DIM]: IntoDimension<Dim = Dim<[usize; N_DIM]>>,
 {
-    let mut data = BufReader::new(fs::File::open(path).unwrap());
+    let mut data = BufReader::new(fs::File::open(path)?);
 
-    let magic = read_int(&mut data);
+    let magic = read_int(&mut data)?;
     let nd = magic % 256;
     let ty = magic / 256;
-    assert_eq!(nd, N_DIM as u32);
-    assert_eq!(ty, T::MAGIC);
+    if nd != N_DIM as u32 || ty != T::MAGIC {
+        return Err(Error::Format(format!(
+            "{} has magic number {magic}, expected {}",
+            path.display(),
+            T::MAGIC * 256 + N_DIM as u32
+        )));
+    }
 
     let mut shape = [0; N_DIM];
-    shape
-        .iter_mut()
-        .for_each(|d| *d = read_int(&mut data) as usize);
+    for d in shape.iter_mut() {
+        *d = read_int(&mut data)? as usize;
+    }
 
     let mut byte_array = Vec::new();
-    data.read_to_end(&mut byte_array).unwrap();
+    data.read_to_end(&mut byte_array)?;
 
     let parsed: Array1<T> = byte_array
         .into_iter()
@@ -299,16 +334,21 @@ where
         .map(T::from_be_bytes)
         .collect();
 
-    parsed.into_shape(shape).unwrap()
+    let found = vec![parsed.len()];
+    parsed.into_shape(shape).map_err(|_| Error::ShapeMismatch {
+        name: path.display().to_string(),
+        expected: shape.to_vec(),
+        found,
+    })
 }
 
 #[inline]
-fn read_label_file(path: &Path) -> Array1<u8> {
+fn read_label_file(path: &Path) -> Result<Array1<u8>> {
     read_sn3(path)
 }
 
 #[inline]
-fn read_image_file(path: &Path) -> Array3<u8> {
+fn read_image_file(path: &Path) -> Result<Array3<u8>> {
     read_sn3(path)
 }
 
@@ -339,7 +379,7 @@ mod test {
         let n_test = lock.to_owned();
 
         if n_test == 0 {
-            MNIST::download(tmp_path());
+            MNIST::download(tmp_path()).unwrap();
         }
         *lock += 1;
         // Moves data from
@@ -369,7 +409,7 @@ mod test {
         let n = prepare(false);
 
         let path = data_path(n);
-        MNIST::download(&path);
+        MNIST::download(&path).unwrap();
 
         let iter = fs::read_dir(path).unwrap();
         assert_eq!(4, iter.count());
@@ -393,7 +433,7 @@ mod test {
         let n = prepare(true);
 
         let path = data_path(n);
-        let (data, labels) = MNIST::load_data(&path, false);
+        let (data, labels) = MNIST::load_data(&path, false).unwrap();
         // Data inputs
         assert_eq!(10_000, data.len_of(Axis(0)));
         assert_eq!(10_000, labels.len());
@@ -402,7 +442,7 @@ mod test {
         assert_eq!(28, data.len_of(Axis(1)));
         assert_eq!(28, data.len_of(Axis(2)));
 
-        let (data, labels) = MNIST::load_data(&path, true);
+        let (data, labels) = MNIST::load_data(&path, true).unwrap();
         // Data inputs
         assert_eq!(60_000, data.len_of(Axis(0)));
         assert_eq!(60_000, labels.len());
@@ -452,4 +492,30 @@ mod test {
 
         finish(n);
     }
+
+    #[test]
+    fn errors() {
+        let path = std::env::temp_dir().join("rstorch_mnist_errors");
+        let _ = fs::remove_dir_all(&path);
+        let result = MNIST::try_new(&path, false, false);
+        assert!(matches!(result, Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound));
+
+        // Labels written as images: 1 dimension of u8 instead of 3
+        fs::create_dir_all(&path).unwrap();
+        let labels = [0, 0, 8, 1, 0, 0, 0, 2, 3, 7];
+        for (filename, _) in MNIST::RESOURCES {
+            let filename = filename.strip_suffix(".gz").unwrap();
+            fs::write(path.join(filename), labels).unwrap();
+        }
+        let result = MNIST::try_new(&path, false, false);
+        assert!(matches!(result, Err(Error::Format(_))));
+
+        // Truncated labels
+        let mut file = labels.to_vec();
+        file.pop();
+        fs::write(path.join("t10k-labels-idx1-ubyte"), file).unwrap();
+        let result = read_label_file(&path.join("t10k-labels-idx1-ubyte"));
+        assert!(matches!(result, Err(Error::ShapeMismatch { .. })));
+        fs::remove_dir_all(&path).unwrap();
+    }
 }
@@ -1,6 +1,6 @@
 use super::{Dataset, IterableDataset};
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
 use std::{iter::FusedIterator, slice::Iter};
 
 #[derive(Debug)]
@@ -13,15 +13,18 @@ impl<D: Dataset> Shuffler<D> {
     #[inline]
     #[must_use]
     pub(crate) fn new(data: D) -> Self {
-        let mut indices: Vec<_> = (0..data.len()).collect();
-        indices.shuffle(&mut thread_rng());
-
+        let indices = (0..data.len()).collect();
         Shuffler { data, indices }
     }
 
+    /// Permutes the original order, so the permutation only depends on the state of `rng`.
     #[inline]
-    pub(crate) fn shuffle(&mut self) {
-        self.indices.shuffle(&mut thread_rng());
+    pub(crate) fn shuffle<R: Rng>(&mut self, rng: &mut R) {
+        self.indices
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, index)| *index = i);
+        self.indices.shuffle(rng);
     }
 }
 
@@ -75,7 +78,10 @@ impl<D: Dataset> ExactSizeIterator for ShufflerIter<'_, D> {}
 impl<D: Dataset> FusedIterator for ShufflerIter<'_, D> {}
 
 impl<'a, D: 'a + Dataset> IterableDataset<'a> for Shuffler<D> {
-    type Iterator = ShufflerIter<'a, D> where Self::Item: 'a;
+    type Iterator
+        = ShufflerIter<'a, D>
+    where
+        Self::Item: 'a;
 
     #[inline]
     fn iter(&'a self) -> Self::Iterator {
@@ -67,7 +67,10 @@ impl<D: Dataset> ExactSizeIterator for SubsetIter<'_, D> {}
 impl<D: Dataset> FusedIterator for SubsetIter<'_, D> {}
 
 impl<'a, D: 'a + Dataset> IterableDataset<'a> for Subset<D> {
-    type Iterator = SubsetIter<'a, D> where Self::Item: 'a;
+    type Iterator
+        = SubsetIter<'a, D>
+    where
+        Self::Item: 'a;
 
     #[inline]
     fn iter(&'a self) -> Self::Iterator {
@@ -2,6 +2,6 @@ mod data_loader;
 pub mod dataset;
 pub mod sampler;
 
-pub use data_loader::DataLoader;
+pub use data_loader::{DataLoader, DataLoaderState};
 pub use dataset::{Basic, Dataset, IterableDataset};
 pub use sampler::{RandomSampler, Sampler, SequentialSampler};
@@ -14,6 +14,11 @@ pub trait Sampler {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Restores the state the sampler had before its `epoch`-th call to [`Sampler::iter`],
+    /// counting from 0, to resume an interrupted run. Does nothing for samplers without state.
+    #[inline]
+    fn set_epoch(&mut self, _epoch: usize) {}
 }
 
 pub struct SequentialSampler {
@@ -42,14 +47,18 @@ impl Sampler for SequentialSampler {
     }
 }
 
+/// Samples random indices. Its state can only be restored by [`Sampler::set_epoch`] when `rng`
+/// is seeded, e.g. a `SmallRng::seed_from_u64`.
 pub struct RandomSampler<R> {
     size: usize,
     num_samples: usize,
     replacement: bool,
     rng: R,
+    /// State of `rng` at creation, replayed by `set_epoch`
+    initial_rng: R,
 }
 
-impl<R: Rng> RandomSampler<R> {
+impl<R: Rng + Clone> RandomSampler<R> {
     #[inline]
     #[must_use]
     pub fn new(size: usize, replacement: bool, num_samples: usize, rng: R) -> Self {
@@ -57,6 +66,7 @@ impl<R: Rng> RandomSampler<R> {
             size,
             num_samples,
             replacement,
+            initial_rng: rng.clone(),
             rng,
         }
     }
@@ -68,12 +78,7 @@ impl<R: Rng> RandomSampler<R> {
         replacement: bool,
         num_samples: usize,
     ) -> RandomSampler<ThreadRng> {
-        RandomSampler {
-            size,
-            num_samples,
-            replacement,
-            rng: ThreadRng::default(),
-        }
+        RandomSampler::new(size, replacement, num_samples, ThreadRng::default())
     }
 }
 
@@ -210,6 +215,18 @@ impl<R: Rng + Clone> Sampler for RandomSampler<R> {
     fn len(&self) -> usize {
         self.size
     }
+
+    fn set_epoch(&mut self, epoch: usize) {
+        self.rng = self.initial_rng.clone();
+        for _ in 0..epoch {
+            RandomSamplerIter::move_rng(
+                self.size,
+                self.replacement,
+                self.num_samples,
+                &mut self.rng,
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -240,6 +257,18 @@ mod tests {
         count
     }
 
+    #[test]
+    fn random_sampler_set_epoch() {
+        let mut sampler = RandomSampler::new(20, false, 20, SmallRng::seed_from_u64(SEED));
+        let epochs: Vec<Vec<_>> = (0..3).map(|_| sampler.iter().collect()).collect();
+        assert_ne!(epochs[1], epochs[2]);
+
+        sampler.set_epoch(2);
+        assert_eq!(epochs[2], sampler.iter().collect::<Vec<_>>());
+        sampler.set_epoch(1);
+        assert_eq!(epochs[1], sampler.iter().collect::<Vec<_>>());
+    }
+
     #[test]
     fn random_sampler_no_replacement_same_lenght_and_samples() {
         let mut sampler = RandomSampler::new(100, true, 100, SmallRng::seed_from_u64(SEED));
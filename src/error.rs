@@ -0,0 +1,108 @@
+use std::fmt;
+use std::io;
+
+/// Error of the fallible operations of the crate, like reading or writing files.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Io(io::Error),
+    /// File that could not be downloaded from any of its mirrors
+    Download(String),
+    /// Downloaded file whose MD5 digest is not the expected one
+    Checksum {
+        file: String,
+        expected: String,
+        found: String,
+    },
+    /// File that does not follow the expected format
+    Format(String),
+    /// Names of a state dict that do not match the parameters of a module
+    StateDictKeys {
+        missing: Vec<String>,
+        unexpected: Vec<String>,
+    },
+    /// Array whose shape does not match the one expected
+    ShapeMismatch {
+        name: String,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+    /// Input shape that a layer cannot take, the layer being given by its path in the model and
+    /// its type, like `2 (Linear)`
+    IncompatibleShape {
+        layer: String,
+        input: Vec<usize>,
+        reason: String,
+    },
+    /// Method called in the wrong state, like a backward pass without a forward pass
+    State(String),
+    /// Argument outside of the accepted values
+    InvalidArgument(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "IO error: {e}"),
+            Error::Download(msg) => write!(f, "Download failed: {msg}"),
+            Error::Checksum {
+                file,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Checksum mismatch for {file}: expected {expected}, found {found}"
+            ),
+            Error::Format(msg) => write!(f, "Invalid format: {msg}"),
+            Error::StateDictKeys {
+                missing,
+                unexpected,
+            } => {
+                write!(f, "State dict does not match the module")?;
+                if !missing.is_empty() {
+                    write!(f, ", missing keys: {}", missing.join(", "))?;
+                }
+                if !unexpected.is_empty() {
+                    write!(f, ", unexpected keys: {}", unexpected.join(", "))?;
+                }
+                Ok(())
+            }
+            Error::ShapeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Shape mismatch for {name}: expected {expected:?}, found {found:?}"
+            ),
+            Error::IncompatibleShape {
+                layer,
+                input,
+                reason,
+            } => write!(
+                f,
+                "Layer {layer} cannot take an input of shape {input:?}: {reason}"
+            ),
+            Error::State(msg) => write!(f, "Invalid state: {msg}"),
+            Error::InvalidArgument(msg) => write!(f, "Invalid argument: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
@@ -0,0 +1,39 @@
+use ndarray::prelude::*;
+use ndarray::NdFloat;
+use ndarray_rand::rand_distr::Normal;
+use ndarray_rand::RandomExt;
+use std::iter::Sum;
+
+/// Floating point type of the elements processed by modules, losses and optimizers. Implemented
+/// for `f32` and `f64`.
+pub trait Float: NdFloat + Default + Sum {
+    fn from_f64(value: f64) -> Self;
+
+    #[inline]
+    fn from_usize(value: usize) -> Self {
+        Self::from_f64(value as f64)
+    }
+
+    /// Samples an array of the given shape from a normal distribution. Panics if the standard
+    /// deviation is not finite.
+    fn random_normal(shape: &[usize], mean: f64, std: f64) -> ArrayD<Self>;
+}
+
+macro_rules! float {
+    ($t:ty) => {
+        impl Float for $t {
+            #[inline]
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+
+            #[inline]
+            fn random_normal(shape: &[usize], mean: f64, std: f64) -> ArrayD<Self> {
+                Array::random(shape, Normal::new(mean as $t, std as $t).unwrap())
+            }
+        }
+    };
+}
+
+float!(f32);
+float!(f64);
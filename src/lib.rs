@@ -1,5 +1,9 @@
 #![allow(dead_code)]
+pub mod autograd;
+pub mod checkpoint;
 pub mod data;
+mod error;
+mod float;
 mod iterator;
 pub mod loss;
 mod model;
@@ -7,11 +11,19 @@ pub mod module;
 pub mod optim;
 pub mod utils;
 
+pub use autograd::Tensor;
 #[cfg(feature = "dataset_hub")]
 pub use data::dataset::hub;
-pub use loss::CrossEntropyLoss;
-pub use module::{Identity, Linear, ReLU, SafeModule, Sequential, Softmax};
-pub use optim::SGD;
+pub use error::{Error, Result};
+pub use float::Float;
+pub use loss::{CrossEntropyLoss, NLLLoss};
+pub use module::{
+    AdaptiveAvgPool2d, AlphaDropout, AvgPool2d, BatchNorm1d, BatchNorm2d, Conv2d, Dropout,
+    Dropout2d, Flatten, GroupNorm, Identity, LayerNorm, LeakyReLU, Linear, LogSoftmax, MaxPool2d,
+    Mish, PReLU, RMSNorm, ReLU, SafeModule, Sequential, SiLU, Sigmoid, Softmax, Softplus,
+    StateDict, Tanh, ELU, GELU,
+};
+pub use optim::{Adam, AdamW, RMSprop, SGD};
 
 mod macros {
     #[doc(hidden)]
@@ -52,6 +64,7 @@ mod macros {
 
 pub mod prelude {
     // traits
+    pub use crate::float::Float;
     pub use crate::module::init::InitParameters;
     pub use crate::module::Module;
 
@@ -60,7 +73,7 @@ pub mod prelude {
 
     pub use crate::loss::Loss;
 
-    pub use crate::optim::Optimizer;
+    pub use crate::optim::{LrScheduler, Optimizer};
 
     // macros
     pub use crate::{safe, sequential};
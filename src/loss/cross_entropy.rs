@@ -1,16 +1,16 @@
-use crate::module::activation::softmax::softmax;
+use crate::module::activation::softmax::log_softmax;
+use crate::module::saved;
+use crate::{Float, Result};
 
 use super::Loss;
 use ndarray::prelude::*;
 
-const EPSILON: f64 = f64::EPSILON;
-
-pub struct CrossEntropyLoss {
-    pred: Option<Array2<f64>>,
-    truth: Option<Array2<f64>>,
+pub struct CrossEntropyLoss<F = f64> {
+    pred: Option<ArrayD<F>>,
+    truth: Option<ArrayD<F>>,
 }
 
-impl CrossEntropyLoss {
+impl<F> CrossEntropyLoss<F> {
     #[inline]
     #[must_use]
     pub fn new() -> Self {
@@ -21,28 +21,29 @@ impl CrossEntropyLoss {
     }
 }
 
-impl Default for CrossEntropyLoss {
+impl<F> Default for CrossEntropyLoss<F> {
     #[inline]
-    #[must_use]
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Loss for CrossEntropyLoss {
+impl<F: Float> Loss<F> for CrossEntropyLoss<F> {
     #[inline]
-    fn forward(&mut self, pred: Array2<f64>, truth: Array2<f64>) -> f64 {
-        let batch_size = pred.nrows() as f64;
+    fn forward(&mut self, pred: ArrayD<F>, truth: ArrayD<F>) -> F {
+        let batch_size = F::from_usize(pred.shape()[0]);
 
-        let pred = softmax(pred, Axis(1)); // Default axis = 1
-        let loss = -(&truth * pred.mapv(f64::ln)).sum() / batch_size;
-        self.pred = Some(pred);
+        let log_pred = log_softmax(pred, Axis(1)); // Default axis = 1
+        let loss = -(&truth * &log_pred).sum() / batch_size;
+        self.pred = Some(log_pred.mapv(F::exp));
         self.truth = Some(truth);
         loss
     }
 
     #[inline]
-    fn backward(&mut self) -> Array2<f64> {
-        -(self.truth.take().unwrap() - self.pred.take().unwrap())
+    fn backward(&mut self) -> Result<ArrayD<F>> {
+        let truth = saved(&mut self.truth, "CrossEntropyLoss")?;
+        let pred = saved(&mut self.pred, "CrossEntropyLoss")?;
+        Ok(pred - truth)
     }
 }
@@ -1,11 +1,16 @@
+use crate::{Float, Result};
 use ndarray::prelude::*;
 
 mod cross_entropy;
+mod nll;
 pub use cross_entropy::CrossEntropyLoss;
+pub use nll::NLLLoss;
 
-pub trait Loss {
-    /// (batch_size, input_size)
-    fn forward(&mut self, input: Array2<f64>, truth: Array2<f64>) -> f64;
-    /// (batch_size, input_size)
-    fn backward(&mut self) -> Array2<f64>;
+pub trait Loss<F: Float = f64> {
+    /// (batch_size, *input_shape)
+    fn forward(&mut self, input: ArrayD<F>, truth: ArrayD<F>) -> F;
+    /// (batch_size, *input_shape)
+    ///
+    /// Fails with [`Error::State`](crate::Error::State) without a previous forward pass.
+    fn backward(&mut self) -> Result<ArrayD<F>>;
 }
@@ -0,0 +1,41 @@
+use crate::module::saved;
+use crate::{Float, Result};
+
+use super::Loss;
+use ndarray::prelude::*;
+
+/// Negative log likelihood of the one-hot `truth` given log probabilities, e.g. the output of a
+/// [`LogSoftmax`](crate::module::activation::LogSoftmax), averaged over the batch.
+pub struct NLLLoss<F = f64> {
+    truth: Option<ArrayD<F>>,
+}
+
+impl<F> NLLLoss<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self { truth: None }
+    }
+}
+
+impl<F> Default for NLLLoss<F> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Float> Loss<F> for NLLLoss<F> {
+    #[inline]
+    fn forward(&mut self, pred: ArrayD<F>, truth: ArrayD<F>) -> F {
+        let batch_size = F::from_usize(pred.shape()[0]);
+        let loss = -(&truth * &pred).sum() / batch_size;
+        self.truth = Some(truth);
+        loss
+    }
+
+    #[inline]
+    fn backward(&mut self) -> Result<ArrayD<F>> {
+        Ok(-saved(&mut self.truth, "NLLLoss")?)
+    }
+}
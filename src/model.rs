@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use crate::Result;
 use ndarray::prelude::*;
 
 pub struct BasicModel<M, L, O> {
@@ -7,7 +8,7 @@ pub struct BasicModel<M, L, O> {
     optim: O,
 }
 
-impl<M: Module, L: Loss, O: Optimizer> BasicModel<M, L, O> {
+impl<M, L, O> BasicModel<M, L, O> {
     fn new(module: M, loss: L, optim: O) -> Self {
         Self {
             module,
@@ -16,17 +17,28 @@ impl<M: Module, L: Loss, O: Optimizer> BasicModel<M, L, O> {
         }
     }
 
-    fn step(&mut self, input: Array2<f64>, truth: Array2<f64>) {
+    fn step<F>(&mut self, input: ArrayD<F>, truth: ArrayD<F>) -> Result<()>
+    where
+        F: Float,
+        M: Module<F>,
+        L: Loss<F>,
+        O: Optimizer<F>,
+    {
+        self.optim.zero_grad(&mut self.module);
         let input = self.module.forward(input);
         self.loss.forward(input, truth);
 
-        let gradient = self.loss.backward();
-        self.module.backward(gradient);
+        let gradient = self.loss.backward()?;
+        self.module.backward(gradient)?;
 
         self.optim.step(&mut self.module);
+        Ok(())
     }
 
-    fn predict(&mut self, input: Array2<f64>) -> Array2<f64> {
+    fn predict<F: Float>(&mut self, input: ArrayD<F>) -> ArrayD<F>
+    where
+        M: Module<F>,
+    {
         self.module.forward(input)
     }
 }
@@ -0,0 +1,84 @@
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// Exponential linear unit: `x` for positive inputs and `alpha * (exp(x) - 1)` otherwise.
+#[derive(Debug)]
+pub struct ELU<F = f64> {
+    alpha: f64,
+    prev_input: Option<ArrayD<F>>,
+}
+
+impl<F> Default for ELU<F> {
+    /// Alpha of 1.
+    #[inline]
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl<F> ELU<F> {
+    #[inline]
+    #[must_use]
+    pub fn new(alpha: f64) -> Self {
+        ELU {
+            alpha,
+            prev_input: None,
+        }
+    }
+}
+
+impl<F: Float> Module<F> for ELU<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let alpha = F::from_f64(self.alpha);
+        let output = input.mapv(|x| match x > F::zero() {
+            true => x,
+            false => alpha * x.exp_m1(),
+        });
+        self.prev_input = Some(input);
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let prev_input = saved(&mut self.prev_input, "ELU")?;
+        let alpha = F::from_f64(self.alpha);
+        Ok(gradient
+            * prev_input.mapv(|x| match x > F::zero() {
+                true => F::one(),
+                false => alpha * x.exp(),
+            }))
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let mut module = ELU::new(2.0);
+        let data = array![[1.0, 0.0], [-1.0, -1000.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[1.0, 0.0], [-1.264241, -2.0]].into_dyn();
+
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[1.0, -2.0], [0.5, -0.1], [-0.3, 3.0]].into_dyn();
+        check_gradients(&mut ELU::new(1.5), &data);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_2_SQRT_PI};
+
+/// Coefficient of the cubic term of the tanh approximation
+const COEFFICIENT: f64 = 0.044715;
+
+/// Gaussian error linear unit, `x * Φ(x)` where `Φ` is the cumulative distribution function of
+/// the standard normal distribution, computed exactly with `erf` or approximated with `tanh`.
+#[derive(Debug, Default)]
+pub struct GELU<F = f64> {
+    tanh: bool,
+    prev_input: Option<ArrayD<F>>,
+}
+
+impl<F> GELU<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        GELU {
+            tanh: false,
+            prev_input: None,
+        }
+    }
+
+    /// Approximates `Φ(x)` as `(1 + tanh(sqrt(2 / π) * (x + 0.044715 * x^3))) / 2`.
+    #[inline]
+    #[must_use]
+    pub fn new_tanh() -> Self {
+        GELU {
+            tanh: true,
+            prev_input: None,
+        }
+    }
+}
+
+/// Value and derivative of GELU at `x`.
+fn gelu(x: f64, tanh: bool) -> (f64, f64) {
+    match tanh {
+        true => {
+            let sqrt_2_over_pi = FRAC_2_SQRT_PI * FRAC_1_SQRT_2;
+            let t = (sqrt_2_over_pi * (x + COEFFICIENT * x.powi(3))).tanh();
+            let inner = sqrt_2_over_pi * (1.0 + 3.0 * COEFFICIENT * x * x);
+            (
+                0.5 * x * (1.0 + t),
+                0.5 * (1.0 + t) + 0.5 * x * (1.0 - t * t) * inner,
+            )
+        }
+        false => {
+            let cdf = 0.5 * (1.0 + libm::erf(x * FRAC_1_SQRT_2));
+            let pdf = (-0.5 * x * x).exp() * 0.5 * FRAC_2_SQRT_PI * FRAC_1_SQRT_2;
+            (x * cdf, cdf + x * pdf)
+        }
+    }
+}
+
+impl<F: Float> Module<F> for GELU<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let output = input.mapv(|x| F::from_f64(gelu(x.to_f64().unwrap(), self.tanh).0));
+        self.prev_input = Some(input);
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let prev_input = saved(&mut self.prev_input, "GELU")?;
+        let derivative = prev_input.mapv(|x| F::from_f64(gelu(x.to_f64().unwrap(), self.tanh).1));
+        Ok(gradient * derivative)
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let data = array![[0.0, 1.0], [-1.0, 3.0], [-10.0, 10.0]].into_dyn();
+        let result = GELU::new().forward(data.clone());
+        let expected = array![[0.0, 0.841345], [-0.158655, 2.995950], [0.0, 10.0]].into_dyn();
+        crate::assert_array_eq!(result, expected);
+
+        let result = GELU::new_tanh().forward(data);
+        let expected = array![[0.0, 0.841192], [-0.158808, 2.996363], [0.0, 10.0]].into_dyn();
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[1.0, -2.0], [0.5, -0.1], [-0.3, 3.0]].into_dyn();
+        check_gradients(&mut GELU::new(), &data);
+        check_gradients(&mut GELU::new_tanh(), &data);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::module::{Module, Parameters};
+use crate::{Float, Result};
 use ndarray::prelude::*;
 
 #[derive(Debug, Default)]
@@ -12,20 +13,25 @@ impl Identity {
     }
 }
 
-impl Module for Identity {
+impl<F: Float> Module<F> for Identity {
     #[inline]
-    fn forward(&mut self, input: Array2<f64>) -> Array2<f64> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
         input
     }
 
     #[inline]
-    fn backward(&mut self, gradient: Array2<f64>) -> Array2<f64> {
-        gradient
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        Ok(gradient)
     }
 
-    fn parameters(&mut self) -> crate::module::Parameters<'_> {
+    fn parameters(&mut self) -> Parameters<'_, F> {
         Parameters::new(0)
     }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
 }
 
 #[cfg(test)]
@@ -35,7 +41,7 @@ mod tests {
     #[test]
     fn forward() {
         let mut module = Identity::new();
-        let data = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]];
+        let data = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]].into_dyn();
         let result = module.forward(data.clone());
 
         crate::assert_array_eq!(result, data);
@@ -44,8 +50,8 @@ mod tests {
     #[test]
     fn backward() {
         let mut module = Identity::new();
-        let data = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]];
-        let result = module.backward(data.clone());
+        let data = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]].into_dyn();
+        let result = module.backward(data.clone()).unwrap();
 
         crate::assert_array_eq!(result, data);
     }
@@ -0,0 +1,78 @@
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// [`ReLU`](super::ReLU) that multiplies negative inputs by `negative_slope` instead of zeroing
+/// them.
+#[derive(Debug)]
+pub struct LeakyReLU<F = f64> {
+    negative_slope: f64,
+    prev_input: Option<ArrayD<F>>,
+}
+
+impl<F> Default for LeakyReLU<F> {
+    /// Negative slope of 0.01.
+    #[inline]
+    fn default() -> Self {
+        Self::new(0.01)
+    }
+}
+
+impl<F> LeakyReLU<F> {
+    #[inline]
+    #[must_use]
+    pub fn new(negative_slope: f64) -> Self {
+        LeakyReLU {
+            negative_slope,
+            prev_input: None,
+        }
+    }
+}
+
+impl<F: Float> Module<F> for LeakyReLU<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let slope = F::from_f64(self.negative_slope);
+        let output = input.mapv(|x| if x > F::zero() { x } else { x * slope });
+        self.prev_input = Some(input);
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let prev_input = saved(&mut self.prev_input, "LeakyReLU")?;
+        let slope = F::from_f64(self.negative_slope);
+        Ok(gradient * prev_input.mapv(|x| if x > F::zero() { F::one() } else { slope }))
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let mut module = LeakyReLU::new(0.1);
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[1.0, 2.0], [3.0, -0.4], [-0.5, -0.6]].into_dyn();
+
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[1.0, -2.0], [0.5, -0.1], [-0.3, 3.0]].into_dyn();
+        check_gradients(&mut LeakyReLU::default(), &data);
+    }
+}
@@ -0,0 +1,111 @@
+use super::softmax::log_softmax;
+use crate::module::{incompatible, saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// `log(softmax(x))` along an axis, computed with the log-sum-exp trick so that it stays finite
+/// for large inputs. Followed by an [`NLLLoss`](crate::loss::NLLLoss), it is equivalent to a
+/// [`CrossEntropyLoss`](crate::CrossEntropyLoss).
+#[derive(Debug)]
+pub struct LogSoftmax<F = f64> {
+    output: Option<ArrayD<F>>,
+    axis: Axis,
+}
+
+impl<F> Default for LogSoftmax<F> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> LogSoftmax<F> {
+    #[inline]
+    #[must_use]
+    pub fn with_axis(axis: usize) -> Self {
+        LogSoftmax {
+            output: None,
+            axis: Axis(axis),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_axis(1)
+    }
+}
+
+impl<F: Float> Module<F> for LogSoftmax<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let output = log_softmax(input, self.axis);
+        self.output = Some(output.clone());
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let output = saved(&mut self.output, "LogSoftmax")?;
+        // Jacobian-vector product of every slice along the axis: g - softmax * sum(g)
+        let sum = gradient.sum_axis(self.axis).insert_axis(self.axis);
+        Ok(gradient - output.mapv(F::exp) * sum)
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        match self.axis.index() < input_shape.len() {
+            true => Ok(input_shape.to_vec()),
+            false => {
+                let reason = format!("expected at least {} dimensions", self.axis.index() + 1);
+                Err(incompatible("LogSoftmax", input_shape, reason))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+    use crate::loss::{Loss, NLLLoss};
+    use crate::module::check_gradients;
+    use crate::CrossEntropyLoss;
+
+    #[test]
+    fn forward() {
+        let mut module = LogSoftmax::new();
+        let result = module.forward(array![[1.0, 1.0], [1e4, 0.0]].into_dyn());
+        let expected = array![[-2f64.ln(), -2f64.ln()], [0.0, -1e4]].into_dyn();
+        assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let input = array![[1.0, -2.0, 0.5], [3.0, 0.0, -1.0]].into_dyn();
+        check_gradients(&mut LogSoftmax::new(), &input);
+        check_gradients(&mut LogSoftmax::with_axis(0), &input);
+    }
+
+    #[test]
+    fn nll_loss() {
+        let input = array![[1.0f64, -2.0, 0.5], [300.0, 0.0, -1000.0]].into_dyn();
+        let truth = array![[0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].into_dyn();
+        let mut module = LogSoftmax::new();
+        let mut loss = NLLLoss::new();
+        let mut cross_entropy = CrossEntropyLoss::new();
+
+        let result = loss.forward(module.forward(input.clone()), truth.clone());
+        let expected = cross_entropy.forward(input, truth);
+        assert!((result - expected).abs() < 1e-9, "{result} != {expected}");
+        assert!((result - (1300.0 + 3.5046) / 2.0).abs() < 1e-3, "{result}");
+
+        let result = module.backward(loss.backward().unwrap()).unwrap();
+        let expected = cross_entropy.backward().unwrap();
+        assert_array_eq!(result, expected);
+    }
+}
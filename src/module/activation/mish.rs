@@ -0,0 +1,74 @@
+use super::sigmoid::sigmoid;
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// `x * tanh(softplus(x))`.
+#[derive(Debug, Default)]
+pub struct Mish<F = f64> {
+    prev_input: Option<ArrayD<F>>,
+}
+
+impl<F> Mish<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Mish { prev_input: None }
+    }
+}
+
+/// `tanh(log(1 + exp(x)))`, without overflowing for large inputs.
+#[inline]
+fn tanh_softplus<F: Float>(x: F) -> F {
+    (x.max(F::zero()) + (-x.abs()).exp().ln_1p()).tanh()
+}
+
+impl<F: Float> Module<F> for Mish<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let output = input.mapv(|x| x * tanh_softplus(x));
+        self.prev_input = Some(input);
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let prev_input = saved(&mut self.prev_input, "Mish")?;
+        Ok(gradient
+            * prev_input.mapv(|x| {
+                let t = tanh_softplus(x);
+                t + x * sigmoid(x) * (F::one() - t * t)
+            }))
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let mut module = Mish::new();
+        let data = array![[0.0, 1.0], [-1.0, 1000.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[0.0, 0.865098], [-0.303401, 1000.0]].into_dyn();
+
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[1.0, -2.0], [0.5, -0.1], [-0.3, 3.0]].into_dyn();
+        check_gradients(&mut Mish::new(), &data);
+    }
+}
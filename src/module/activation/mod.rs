@@ -1,7 +1,27 @@
+pub(crate) mod elu;
+pub(crate) mod gelu;
 pub(crate) mod identity;
+pub(crate) mod leaky_relu;
+pub(crate) mod log_softmax;
+pub(crate) mod mish;
+pub(crate) mod prelu;
 pub(crate) mod relu;
+pub(crate) mod sigmoid;
+pub(crate) mod silu;
 pub(crate) mod softmax;
+pub(crate) mod softplus;
+pub(crate) mod tanh;
 
+pub use elu::ELU;
+pub use gelu::GELU;
 pub use identity::Identity;
+pub use leaky_relu::LeakyReLU;
+pub use log_softmax::LogSoftmax;
+pub use mish::Mish;
+pub use prelu::PReLU;
 pub use relu::ReLU;
+pub use sigmoid::Sigmoid;
+pub use silu::SiLU;
 pub use softmax::Softmax;
+pub use softplus::Softplus;
+pub use tanh::Tanh;
@@ -0,0 +1,142 @@
+use crate::module::{channel_sum, incompatible, per_channel, saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// [`LeakyReLU`](super::LeakyReLU) with a learned negative slope (`weight`), either shared by
+/// every element or one per channel of an input of shape `(batch_size, channels, *)`.
+#[derive(Debug)]
+pub struct PReLU<F = f64> {
+    /// (num_parameters)
+    weight: ArrayD<F>,
+
+    prev_input: Option<ArrayD<F>>,
+    grad_weight: ArrayD<F>,
+    weight_requires_grad: bool,
+}
+
+impl<F: Float> Default for PReLU<F> {
+    /// Single slope shared by every element.
+    #[inline]
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl<F: Float> PReLU<F> {
+    #[inline]
+    #[must_use]
+    pub fn new_with_init(num_parameters: usize, init: f64) -> Self {
+        PReLU {
+            weight: ArrayD::from_elem(vec![num_parameters], F::from_f64(init)),
+            prev_input: None,
+            grad_weight: ArrayD::zeros(vec![num_parameters]),
+            weight_requires_grad: true,
+        }
+    }
+
+    /// Slopes initialized to 0.25.
+    #[inline]
+    #[must_use]
+    pub fn new(num_parameters: usize) -> Self {
+        Self::new_with_init(num_parameters, 0.25)
+    }
+
+    /// Slopes broadcast over an input with `ndim` dimensions.
+    #[inline]
+    fn slopes(&self, ndim: usize) -> ArrayD<F> {
+        match self.weight.len() {
+            1 => self.weight.clone().into_shape(vec![1; ndim]).unwrap(),
+            _ => per_channel(&self.weight, ndim),
+        }
+    }
+}
+
+impl<F: Float> Module<F> for PReLU<F> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        if let Err(error) = self.output_shape(input.shape()) {
+            panic!("{error}");
+        }
+        let negative = input.mapv(|x| x.min(F::zero()));
+        let output = input.mapv(|x| x.max(F::zero())) + negative * self.slopes(input.ndim());
+        self.prev_input = Some(input);
+        output
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let prev_input = saved(&mut self.prev_input, "PReLU")?;
+        let n = F::from_usize(prev_input.shape()[0]);
+
+        if self.weight_requires_grad {
+            let product = &gradient * &prev_input.mapv(|x| x.min(F::zero()));
+            let grad_weight = match self.weight.len() {
+                1 => ArrayD::from_elem(vec![1], product.sum()),
+                _ => channel_sum(&product),
+            };
+            self.grad_weight += &(grad_weight / n);
+        }
+
+        let slopes = self.slopes(prev_input.ndim());
+        let positive = prev_input.mapv(|x| if x > F::zero() { F::one() } else { F::zero() });
+        let negative = positive.mapv(|x| F::one() - x) * slopes;
+        Ok(gradient * (positive + negative))
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(1).add_with_requires_grad(
+            "weight",
+            &mut self.weight,
+            &mut self.grad_weight,
+            &mut self.weight_requires_grad,
+        )
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        let channels = self.weight.len();
+        match input_shape {
+            [] => Err(incompatible(
+                "PReLU",
+                input_shape,
+                "expected a batch dimension",
+            )),
+            _ if channels == 1 => Ok(input_shape.to_vec()),
+            [_, c, ..] if *c == channels => Ok(input_shape.to_vec()),
+            _ => {
+                let reason = format!("expected a shape (batch_size, {channels}, *)");
+                Err(incompatible("PReLU", input_shape, reason))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let mut module = PReLU::new(1);
+        let data = array![[1.0, -2.0], [-4.0, 0.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[1.0, -0.5], [-1.0, 0.0]].into_dyn();
+        crate::assert_array_eq!(result, expected);
+
+        let mut module = PReLU::new(2);
+        module.weight = array![0.5, 2.0].into_dyn();
+        let result = module.forward(array![[[-1.0, 1.0], [-1.0, 3.0]]].into_dyn());
+        let expected = array![[[-0.5, 1.0], [-2.0, 3.0]]].into_dyn();
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[[1.0, -2.0], [0.5, -0.1]], [[-0.3, 3.0], [-1.5, 2.0]]].into_dyn();
+        check_gradients(&mut PReLU::new(1), &data);
+        check_gradients(&mut PReLU::new_with_init(2, 0.1), &data);
+
+        let mut module = PReLU::<f64>::new(2);
+        assert_eq!(1, module.parameters().iter().count());
+        assert!(module.output_shape(&[4, 2, 7]).is_ok());
+        assert!(module.output_shape(&[4, 3]).is_err());
+    }
+}
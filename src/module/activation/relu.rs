@@ -1,34 +1,65 @@
-use crate::module::{Module, Parameters};
+use crate::autograd::Tensor;
+use crate::module::{Autograd, AutogradModule, Module, Parameters};
+use crate::{Float, Result};
 use ndarray::prelude::*;
 
-#[derive(Debug, Default)]
-pub struct ReLU {
-    prev_input: Option<Array2<f64>>,
+/// Forward pass of [`ReLU`], whose backward pass is derived by [`Autograd`].
+#[derive(Debug)]
+struct ReLUTensors;
+
+impl<F: Float> AutogradModule<F> for ReLUTensors {
+    #[inline]
+    fn forward(&mut self, input: &Tensor<F>) -> Tensor<F> {
+        input.relu()
+    }
+
+    #[inline]
+    fn tensors(&mut self) -> Vec<(&str, &mut Tensor<F>)> {
+        Vec::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct ReLU<F: Float = f64> {
+    module: Autograd<ReLUTensors, F>,
 }
 
-impl ReLU {
+impl<F: Float> ReLU<F> {
     #[inline]
     #[must_use]
     pub fn new() -> Self {
-        ReLU { prev_input: None }
+        ReLU {
+            module: Autograd::new(ReLUTensors),
+        }
     }
 }
 
-impl Module for ReLU {
+impl<F: Float> Default for ReLU<F> {
     #[inline]
-    fn forward(&mut self, input: Array2<f64>) -> Array2<f64> {
-        self.prev_input = Some(input.clone());
-        input.mapv(|x| x.max(0.0))
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Float> Module<F> for ReLU<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        self.module.forward(input)
     }
 
     #[inline]
-    fn backward(&mut self, gradient: Array2<f64>) -> Array2<f64> {
-        gradient * self.prev_input.take().unwrap().mapv(|x| f64::from(x > 0.0))
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        self.module.backward(gradient)
     }
 
-    fn parameters(&mut self) -> Parameters<'_> {
+    fn parameters(&mut self) -> Parameters<'_, F> {
         Parameters::new(0)
     }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
 }
 
 #[cfg(test)]
@@ -38,9 +69,9 @@ mod tests {
     #[test]
     fn forward() {
         let mut module = ReLU::new();
-        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]];
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]].into_dyn();
         let result = module.forward(data);
-        let expected = array![[1.0, 2.0], [3.0, 0.0], [0.0, 0.0]];
+        let expected = array![[1.0, 2.0], [3.0, 0.0], [0.0, 0.0]].into_dyn();
 
         crate::assert_array_eq!(result, expected);
     }
@@ -48,10 +79,10 @@ mod tests {
     #[test]
     fn backward() {
         let mut module = ReLU::new();
-        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]];
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]].into_dyn();
         module.forward(data);
-        let result = module.backward(Array::ones((3, 2)));
-        let expected = array![[1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+        let result = module.backward(ArrayD::ones(vec![3, 2])).unwrap();
+        let expected = array![[1.0, 1.0], [1.0, 0.0], [0.0, 0.0]].into_dyn();
 
         crate::assert_array_eq!(result, expected);
     }
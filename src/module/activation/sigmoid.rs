@@ -0,0 +1,72 @@
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// `1 / (1 + exp(-x))`, without overflowing for large negative inputs.
+#[inline]
+pub(crate) fn sigmoid<F: Float>(x: F) -> F {
+    match x >= F::zero() {
+        true => (F::one() + (-x).exp()).recip(),
+        false => x.exp() / (F::one() + x.exp()),
+    }
+}
+
+/// Applies `1 / (1 + exp(-x))` elementwise.
+#[derive(Debug, Default)]
+pub struct Sigmoid<F = f64> {
+    output: Option<ArrayD<F>>,
+}
+
+impl<F> Sigmoid<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Sigmoid { output: None }
+    }
+}
+
+impl<F: Float> Module<F> for Sigmoid<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let output = input.mapv(sigmoid);
+        self.output = Some(output.clone());
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let output = saved(&mut self.output, "Sigmoid")?;
+        Ok(gradient * output.mapv(|y| y * (F::one() - y)))
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let mut module = Sigmoid::new();
+        let data = array![[0.0, 2.0], [-1000.0, 1000.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[0.5, 0.880797], [0.0, 1.0]].into_dyn();
+
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[1.0, -2.0], [0.5, 0.0], [-0.3, 3.0]].into_dyn();
+        check_gradients(&mut Sigmoid::new(), &data);
+    }
+}
@@ -0,0 +1,68 @@
+use super::sigmoid::sigmoid;
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// Sigmoid linear unit, also known as swish: `x * sigmoid(x)`.
+#[derive(Debug, Default)]
+pub struct SiLU<F = f64> {
+    prev_input: Option<ArrayD<F>>,
+}
+
+impl<F> SiLU<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        SiLU { prev_input: None }
+    }
+}
+
+impl<F: Float> Module<F> for SiLU<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let output = input.mapv(|x| x * sigmoid(x));
+        self.prev_input = Some(input);
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let prev_input = saved(&mut self.prev_input, "SiLU")?;
+        Ok(gradient
+            * prev_input.mapv(|x| {
+                let s = sigmoid(x);
+                s * (F::one() + x * (F::one() - s))
+            }))
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let mut module = SiLU::new();
+        let data = array![[0.0, 1.0], [-1.0, -1000.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[0.0, 0.731059], [-0.268941, 0.0]].into_dyn();
+
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[1.0, -2.0], [0.5, -0.1], [-0.3, 3.0]].into_dyn();
+        check_gradients(&mut SiLU::new(), &data);
+    }
+}
@@ -1,22 +1,22 @@
-use crate::module::{Module, Parameters};
+use crate::module::{incompatible, saved, Module, Parameters};
+use crate::{Float, Result};
 use ndarray::prelude::*;
 use std::cmp::Ordering;
 
 #[derive(Debug)]
-pub struct Softmax {
-    output: Option<Array2<f64>>,
+pub struct Softmax<F = f64> {
+    output: Option<ArrayD<F>>,
     axis: Axis,
 }
 
-impl Default for Softmax {
+impl<F> Default for Softmax<F> {
     #[inline]
-    #[must_use]
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Softmax {
+impl<F> Softmax<F> {
     #[inline]
     #[must_use]
     pub fn with_axis(axis: usize) -> Self {
@@ -34,7 +34,7 @@ impl Softmax {
 }
 
 #[inline]
-fn max(a: f64, b: &f64) -> f64 {
+fn max<F: Float>(a: F, b: &F) -> F {
     match a.partial_cmp(b).unwrap() {
         Ordering::Less => *b,
         Ordering::Equal => a,
@@ -42,73 +42,129 @@ fn max(a: f64, b: &f64) -> f64 {
     }
 }
 
-pub fn softmax(input: Array2<f64>, axis: Axis) -> Array2<f64> {
+pub fn softmax<F: Float>(input: ArrayD<F>, axis: Axis) -> ArrayD<F> {
     // Broadcasting fails, but inserting axis makes it work properly
     let max_axis = input
-        .map_axis(axis, |axis| axis.iter().fold(f64::MIN, max))
+        .map_axis(axis, |axis| axis.iter().fold(F::min_value(), max))
         .insert_axis(axis);
-    let exp = (input - max_axis).mapv(f64::exp);
+    let exp = (input - max_axis).mapv(F::exp);
     let sum_axis = exp.sum_axis(axis).insert_axis(axis);
     exp / sum_axis
 }
 
-impl Module for Softmax {
+/// `log(softmax(input))` computed with the log-sum-exp trick, so it stays finite for large inputs.
+pub fn log_softmax<F: Float>(input: ArrayD<F>, axis: Axis) -> ArrayD<F> {
+    let max_axis = input
+        .map_axis(axis, |axis| axis.iter().fold(F::min_value(), max))
+        .insert_axis(axis);
+    let shifted = input - max_axis;
+    let log_sum = shifted
+        .mapv(F::exp)
+        .sum_axis(axis)
+        .mapv(F::ln)
+        .insert_axis(axis);
+    shifted - log_sum
+}
+
+impl<F: Float> Module<F> for Softmax<F> {
     #[inline]
-    fn forward(&mut self, input: Array2<f64>) -> Array2<f64> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
         self.output = Some(softmax(input, self.axis));
         self.output.clone().unwrap()
     }
 
     #[inline]
-    fn backward(&mut self, gradient: Array2<f64>) -> Array2<f64> {
-        let output = self.output.take().unwrap();
-
-        let mut jacobian = -output.t().dot(&output);
-        jacobian.diag_mut().into_iter().for_each(|el| {
-            *el = -*el;
-            *el = *el * (1.0 - *el);
-        });
-
-        gradient.dot(&jacobian)
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let output = saved(&mut self.output, "Softmax")?;
+        // Jacobian-vector product of every slice along the axis: s * (g - sum(g * s))
+        let dot = (&gradient * &output)
+            .sum_axis(self.axis)
+            .insert_axis(self.axis);
+        Ok(output * (gradient - dot))
     }
 
-    fn parameters(&mut self) -> Parameters<'_> {
+    fn parameters(&mut self) -> Parameters<'_, F> {
         Parameters::new(0)
     }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        match self.axis.index() < input_shape.len() {
+            true => Ok(input_shape.to_vec()),
+            false => {
+                let reason = format!("expected at least {} dimensions", self.axis.index() + 1);
+                Err(incompatible("Softmax", input_shape, reason))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::assert_array_eq;
+    use crate::module::check_gradients;
 
     #[test]
     fn forward() {
-        let input = array![[1.0, 10.0], [-3.0, 4.0], [5.0, 6.0]];
+        let input = array![[1.0, 10.0], [-3.0, 4.0], [5.0, 6.0]].into_dyn();
         let mut module = Softmax::new();
 
         let output = module.forward(input);
-        assert_array_eq!(array![1.0, 1.0, 1.0], output.sum_axis(Axis(1)));
+        assert_array_eq!(array![1.0, 1.0, 1.0].into_dyn(), output.sum_axis(Axis(1)));
         assert!(output.sum().eq(&3.0), "{} != {}", 3.0, output.sum());
     }
 
     #[test]
     fn forward_other_axis() {
-        let input = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]];
+        let input = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]].into_dyn();
         let mut module = Softmax::with_axis(0);
 
         let output = module.forward(input);
-        assert_array_eq!(array![1.0, 1.0], output.sum_axis(Axis(0)));
+        assert_array_eq!(array![1.0, 1.0].into_dyn(), output.sum_axis(Axis(0)));
         assert!(output.sum().eq(&2.0), "{} != {}", 2.0, output.sum());
     }
 
     #[test]
     fn backward() {
-        let input = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]];
+        let input = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]].into_dyn();
+        let mut module = Softmax::new();
+        module.forward(input.clone());
+        // The outputs of every row sum to one, so a constant gradient does not change them
+        let result = module.backward(ArrayD::ones(vec![3, 2])).unwrap();
+        let expected = ArrayD::zeros(vec![3, 2]);
+        assert_array_eq!(result, expected);
+
+        check_gradients(&mut Softmax::new(), &input);
+        let input = array![[[1.0, -2.0], [0.5, 3.0]], [[-1.0, 0.0], [2.0, 2.5]]].into_dyn();
+        check_gradients(&mut Softmax::with_axis(2), &input);
+    }
+
+    #[test]
+    fn backward_batch_independent() {
+        let input = array![[1.0, 2.0, 0.5], [3.0, -4.0, 1.0]].into_dyn();
+        let gradient = array![[0.3, -1.0, 2.0], [1.5, 0.5, -0.5]].into_dyn();
         let mut module = Softmax::new();
-        module.forward(input);
-        let result = module.backward(Array::ones((3, 2)));
-        let expected = array![[-0.41, -1.55], [-0.41, -1.55], [-0.41, -1.55]];
-        assert_array_eq!(result, expected, 0.01);
+        module.forward(input.clone());
+        let result = module.backward(gradient.clone()).unwrap();
+
+        for i in 0..2 {
+            let row = |x: &ArrayD<f64>| {
+                x.slice_axis(Axis(0), ndarray::Slice::from(i..i + 1))
+                    .to_owned()
+            };
+            module.forward(row(&input));
+            let single = module.backward(row(&gradient)).unwrap();
+            let expected = row(&result);
+            assert_array_eq!(single, expected);
+        }
+    }
+
+    #[test]
+    fn log_softmax_large_inputs() {
+        let input = array![[1000.0, 0.0], [-1000.0, -1000.0]].into_dyn();
+        let result = log_softmax(input, Axis(1));
+        let expected = array![[0.0, -1000.0], [-2f64.ln(), -2f64.ln()]].into_dyn();
+        assert_array_eq!(result, expected);
     }
 }
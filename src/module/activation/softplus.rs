@@ -0,0 +1,106 @@
+use super::sigmoid::sigmoid;
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// Smooth approximation of [`ReLU`](super::ReLU), `log(1 + exp(beta * x)) / beta`, which is
+/// linear once `beta * x` exceeds `threshold` for numerical stability.
+#[derive(Debug)]
+pub struct Softplus<F = f64> {
+    beta: f64,
+    threshold: f64,
+    prev_input: Option<ArrayD<F>>,
+}
+
+impl<F> Default for Softplus<F> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> Softplus<F> {
+    /// Beta of 1 and threshold of 20.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Softplus {
+            beta: 1.0,
+            threshold: 20.0,
+            prev_input: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn beta(mut self, beta: f64) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+impl<F: Float> Module<F> for Softplus<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let (beta, threshold) = (F::from_f64(self.beta), F::from_f64(self.threshold));
+        let output = input.mapv(|x| match beta * x > threshold {
+            true => x,
+            false => (beta * x).exp().ln_1p() / beta,
+        });
+        self.prev_input = Some(input);
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let prev_input = saved(&mut self.prev_input, "Softplus")?;
+        let (beta, threshold) = (F::from_f64(self.beta), F::from_f64(self.threshold));
+        Ok(gradient
+            * prev_input.mapv(|x| match beta * x > threshold {
+                true => F::one(),
+                false => sigmoid(beta * x),
+            }))
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let mut module = Softplus::new();
+        let data = array![[0.0, 1.0], [-1000.0, 1000.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[2f64.ln(), 1.313262], [0.0, 1000.0]].into_dyn();
+        crate::assert_array_eq!(result, expected);
+
+        let mut module = Softplus::new().beta(2.0).threshold(1.0);
+        let result = module.forward(array![0.25, 1.0].into_dyn());
+        let expected = array![0.487038, 1.0].into_dyn();
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[1.0, -2.0], [0.5, -0.1], [-0.3, 3.0]].into_dyn();
+        check_gradients(&mut Softplus::new().beta(1.5), &data);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// Applies the hyperbolic tangent elementwise.
+#[derive(Debug, Default)]
+pub struct Tanh<F = f64> {
+    output: Option<ArrayD<F>>,
+}
+
+impl<F> Tanh<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Tanh { output: None }
+    }
+}
+
+impl<F: Float> Module<F> for Tanh<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let output = input.mapv(F::tanh);
+        self.output = Some(output.clone());
+        output
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let output = saved(&mut self.output, "Tanh")?;
+        Ok(gradient * output.mapv(|y| F::one() - y * y))
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::check_gradients;
+
+    #[test]
+    fn forward() {
+        let mut module = Tanh::new();
+        let data = array![[0.0, 1.0], [-1000.0, 1000.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[0.0, 0.761594], [-1.0, 1.0]].into_dyn();
+
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn backward() {
+        let data = array![[1.0, -2.0], [0.5, 0.0], [-0.3, 3.0]].into_dyn();
+        check_gradients(&mut Tanh::new(), &data);
+    }
+}
@@ -0,0 +1,337 @@
+use crate::autograd::Tensor;
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// Module defined only by its forward pass over [`Tensor`]s. The backward pass is derived from
+/// the operations recorded on the tape once wrapped in an [`Autograd`].
+pub trait AutogradModule<F: Float = f64> {
+    /// (batch_size, *input_shape) -> (batch_size, *output_shape)
+    fn forward(&mut self, input: &Tensor<F>) -> Tensor<F>;
+
+    /// Learnable tensors of the module with their names. [`Autograd`] replaces them with new
+    /// leaves before every forward pass, so any tensor can be returned.
+    fn tensors(&mut self) -> Vec<(&str, &mut Tensor<F>)>;
+}
+
+/// Implements [`Module`] for an [`AutogradModule`], computing `backward` from the tape.
+///
+/// The values and gradients of the parameters are owned by the wrapper and copied into new
+/// leaves of the tape on every forward pass, so the parameters can be read or updated between
+/// `forward` and `backward` without invalidating the pending pass.
+///
+/// To follow the convention of the hand-written modules, the gradient of the parameters is
+/// averaged over the batch, while the gradient with respect to the input is not.
+#[derive(Debug)]
+pub struct Autograd<M, F: Float = f64> {
+    module: M,
+    parms: Vec<ArrayD<F>>,
+    grads: Vec<ArrayD<F>>,
+    requires_grad: Vec<bool>,
+
+    input: Option<Tensor<F>>,
+    output: Option<Tensor<F>>,
+}
+
+impl<M: AutogradModule<F>, F: Float> Autograd<M, F> {
+    #[inline]
+    #[must_use]
+    pub fn new(mut module: M) -> Self {
+        let parms: Vec<_> = module
+            .tensors()
+            .into_iter()
+            .map(|(_, tensor)| tensor.data().clone())
+            .collect();
+        Self {
+            grads: parms
+                .iter()
+                .map(|parm| ArrayD::zeros(parm.raw_dim()))
+                .collect(),
+            requires_grad: vec![true; parms.len()],
+            parms,
+            module,
+            input: None,
+            output: None,
+        }
+    }
+
+    /// The wrapped module, whose tensors hold the values of the parameters as of the last forward
+    /// pass.
+    #[inline]
+    pub fn inner(&self) -> &M {
+        &self.module
+    }
+
+    /// The wrapped module with the current values of the parameters.
+    #[inline]
+    pub fn into_inner(mut self) -> M {
+        self.load_tensors();
+        self.module
+    }
+
+    /// Replaces the tensors of the module by new leaves holding the current parameters. Frozen
+    /// parameters are constants, so the tape does not compute their gradient.
+    fn load_tensors(&mut self) {
+        let tensors = self.module.tensors().into_iter().zip(&self.parms);
+        for (((_, tensor), parm), &requires_grad) in tensors.zip(&self.requires_grad) {
+            *tensor = match requires_grad {
+                true => Tensor::new_requires_grad(parm.clone()),
+                false => Tensor::new(parm.clone()),
+            };
+        }
+    }
+}
+
+impl<M: AutogradModule<F>, F: Float> From<M> for Autograd<M, F> {
+    #[inline]
+    fn from(module: M) -> Self {
+        Self::new(module)
+    }
+}
+
+impl<M: AutogradModule<F>, F: Float> Module<F> for Autograd<M, F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        self.load_tensors();
+        let input = Tensor::new_requires_grad(input);
+        let output = self.module.forward(&input);
+        let result = output.data().clone();
+
+        self.input = Some(input);
+        self.output = Some(output);
+        result
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let input = saved(&mut self.input, "Autograd")?;
+        let output = saved(&mut self.output, "Autograd")?;
+        output.backward_with(gradient);
+        // Releases the graph, which is no longer needed
+        drop(output);
+
+        let n = F::from_usize(input.shape()[0]);
+        // Parameters frozen since the forward pass are still on the tape
+        let tensors = self.module.tensors().into_iter().zip(&self.requires_grad);
+        for (((_, tensor), &requires_grad), grad) in tensors.zip(&mut self.grads) {
+            if let Some(tensor_grad) = tensor.grad().filter(|_| requires_grad) {
+                *grad += &(tensor_grad / n);
+            }
+        }
+        Ok(input.grad().unwrap())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        let names: Vec<_> = self
+            .module
+            .tensors()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let parms = self.parms.iter_mut().zip(&mut self.grads);
+        names.iter().zip(parms).zip(&mut self.requires_grad).fold(
+            Parameters::new(names.len()),
+            |params, ((name, (parm, grad)), requires_grad)| {
+                params.add_with_requires_grad(name, parm, grad, requires_grad)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+    use crate::module::Parameter;
+    use crate::{sequential, Sequential};
+
+    // Minimal modules on the tape, checked against the expected values of Linear and ReLU
+
+    #[derive(Debug)]
+    struct TensorLinear {
+        weight: Tensor,
+        bias: Tensor,
+    }
+
+    impl TensorLinear {
+        fn new() -> Self {
+            Self {
+                weight: Tensor::new_requires_grad(array![[1.0, 0.5]]),
+                bias: Tensor::new_requires_grad(array![10.0]),
+            }
+        }
+    }
+
+    impl AutogradModule for TensorLinear {
+        fn forward(&mut self, input: &Tensor) -> Tensor {
+            &input.matmul(&self.weight.t()) + &self.bias
+        }
+
+        fn tensors(&mut self) -> Vec<(&str, &mut Tensor)> {
+            vec![("weight", &mut self.weight), ("bias", &mut self.bias)]
+        }
+    }
+
+    #[derive(Debug)]
+    struct TensorReLU;
+
+    impl AutogradModule for TensorReLU {
+        fn forward(&mut self, input: &Tensor) -> Tensor {
+            input.relu()
+        }
+
+        fn tensors(&mut self) -> Vec<(&str, &mut Tensor)> {
+            Vec::new()
+        }
+    }
+
+    /// Gradients of the parameters of `module`, in order.
+    fn grads(module: &mut dyn Module) -> Vec<ArrayD<f64>> {
+        module.parameters().iter().map(|p| p.grad.clone()).collect()
+    }
+
+    #[test]
+    fn linear_forward() {
+        let mut module = Autograd::new(TensorLinear::new());
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[12.0], [11.0], [2.0]];
+
+        assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn linear_backward() {
+        let mut module = Autograd::new(TensorLinear::new());
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0], [-7.0, 8.0]].into_dyn();
+        module.forward(data);
+        let result = module.backward(ArrayD::ones(vec![4, 1])).unwrap();
+
+        let expected_grad_w = array![[-2.0, 0.0]];
+        let expected_grad_b = array![1.0];
+        let expected_grad = array![[1.0, 0.5], [1.0, 0.5], [1.0, 0.5], [1.0, 0.5]];
+
+        let grads = grads(&mut module);
+        assert_array_eq!(grads[0], expected_grad_w);
+        assert_array_eq!(grads[1], expected_grad_b);
+        assert_array_eq!(result, expected_grad);
+    }
+
+    #[test]
+    fn linear_backward_twice_accumulates() {
+        let mut module = Autograd::new(TensorLinear::new());
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0], [-7.0, 8.0]].into_dyn();
+        for _ in 0..2 {
+            module.forward(data.clone());
+            module.backward(ArrayD::ones(vec![4, 1])).unwrap();
+        }
+        assert_array_eq!(grads(&mut module)[0], array![[-4.0, 0.0]]);
+
+        module.zero_grad();
+        module.forward(data);
+        module.backward(ArrayD::ones(vec![4, 1])).unwrap();
+        assert_array_eq!(grads(&mut module)[0], array![[-2.0, 0.0]]);
+    }
+
+    #[test]
+    fn zero_grad_between_forward_and_backward() {
+        let mut module = Autograd::new(TensorLinear::new());
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0], [-7.0, 8.0]].into_dyn();
+        module.forward(data.clone());
+        module.backward(ArrayD::ones(vec![4, 1])).unwrap();
+
+        module.forward(data);
+        module.zero_grad();
+        assert_eq!(3, module.num_parameters());
+        let result = module.backward(ArrayD::ones(vec![4, 1])).unwrap();
+
+        let grads = grads(&mut module);
+        assert_array_eq!(grads[0], array![[-2.0, 0.0]]);
+        assert_array_eq!(grads[1], array![1.0]);
+        assert_array_eq!(
+            result,
+            array![[1.0, 0.5], [1.0, 0.5], [1.0, 0.5], [1.0, 0.5]]
+        );
+    }
+
+    #[test]
+    fn update_between_forward_and_backward() {
+        let mut module = Autograd::new(TensorLinear::new());
+        module.forward(array![[1.0, 2.0]].into_dyn());
+        for Parameter { parm, .. } in module.parameters().iter() {
+            parm.fill(0.0);
+        }
+        // The pending pass uses the parameters of the forward pass
+        let result = module.backward(array![[1.0]].into_dyn()).unwrap();
+        assert_array_eq!(result, array![[1.0, 0.5]]);
+
+        let result = module.forward(array![[1.0, 2.0]].into_dyn());
+        assert_array_eq!(result, array![[0.0]]);
+    }
+
+    #[test]
+    fn cloned_tensor() {
+        let mut module = Autograd::new(TensorLinear::new());
+        let weight = module.inner().weight.clone();
+        module.forward(array![[1.0, 2.0]].into_dyn());
+        module.backward(array![[1.0]].into_dyn()).unwrap();
+        assert_eq!(2, module.parameters().iter().count());
+        assert_array_eq!(weight.data().clone(), array![[1.0, 0.5]]);
+    }
+
+    #[test]
+    fn relu() {
+        let mut module = Autograd::new(TensorReLU);
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]].into_dyn();
+        let result = module.forward(data);
+        assert_array_eq!(result, array![[1.0, 2.0], [3.0, 0.0], [0.0, 0.0]]);
+
+        let result = module.backward(ArrayD::ones(vec![3, 2])).unwrap();
+        assert_array_eq!(result, array![[1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]);
+    }
+
+    #[test]
+    fn parameters() {
+        let mut module = Autograd::new(TensorLinear::new());
+        module.forward(array![[1.0, 2.0]].into_dyn());
+        module.backward(array![[1.0]].into_dyn()).unwrap();
+
+        let parms: Vec<_> = module.parameters().iter().collect();
+        assert_eq!(2, parms.len());
+        for Parameter { parm, grad, .. } in parms {
+            assert_eq!(parm.shape(), grad.shape());
+        }
+
+        // Parameters are available even with a pending forward pass
+        module.forward(array![[1.0, 2.0]].into_dyn());
+        assert_eq!(2, module.parameters().iter().count());
+
+        module
+            .parameters()
+            .iter()
+            .next()
+            .unwrap()
+            .set_requires_grad(false);
+        module.zero_grad();
+        module.forward(array![[1.0, 2.0]].into_dyn());
+        module.backward(array![[1.0]].into_dyn()).unwrap();
+        assert_array_eq!(grads(&mut module)[0], array![[0.0, 0.0]]);
+        // The frozen weight is not on the tape
+        assert!(module.inner().weight.grad().is_none());
+        assert!(module.inner().bias.grad().is_some());
+        assert_eq!(1, module.parameters().trainable().count());
+    }
+
+    #[test]
+    fn sequential() {
+        let mut module = sequential!(
+            Autograd::new(TensorLinear::new()),
+            Autograd::new(TensorReLU)
+        );
+        let data = array![[1.0, 2.0], [-30.0, 4.0]].into_dyn();
+        let result = module.forward(data);
+        assert_array_eq!(result, array![[12.0], [0.0]]);
+
+        let result = module.backward(array![[1.0], [1.0]].into_dyn()).unwrap();
+        assert_array_eq!(result, array![[1.0, 0.5], [0.0, 0.0]]);
+    }
+}
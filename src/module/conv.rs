@@ -0,0 +1,688 @@
+use crate::module::init::{InitParameters, KaimingNormal};
+use crate::module::{incompatible, saved, Module, Parameters};
+use crate::{Error, Float, Result};
+use ndarray::prelude::*;
+use ndarray::Slice;
+
+/// Hyperparameters of a [`Conv2d`], given as `(height, width)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conv2dOptions {
+    pub stride: (usize, usize),
+    pub padding: (usize, usize),
+    pub dilation: (usize, usize),
+    pub groups: usize,
+    pub bias: bool,
+}
+
+impl Default for Conv2dOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            stride: (1, 1),
+            padding: (0, 0),
+            dilation: (1, 1),
+            groups: 1,
+            bias: true,
+        }
+    }
+}
+
+impl Conv2dOptions {
+    #[inline]
+    #[must_use]
+    pub fn stride(mut self, stride: usize) -> Self {
+        self.stride = (stride, stride);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = (padding, padding);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn dilation(mut self, dilation: usize) -> Self {
+        self.dilation = (dilation, dilation);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn groups(mut self, groups: usize) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn bias(mut self, bias: bool) -> Self {
+        self.bias = bias;
+        self
+    }
+}
+
+/// Length of the output of a sliding window over an axis of the given length.
+#[inline]
+pub(crate) fn output_len(
+    len: usize,
+    kernel: usize,
+    stride: usize,
+    padding: usize,
+    dilation: usize,
+) -> usize {
+    checked_output_len(len, kernel, stride, padding, dilation).unwrap_or_else(|| {
+        panic!("Kernel of size {kernel} (dilation {dilation}) does not fit an input of size {len} with padding {padding}")
+    })
+}
+
+/// Like [`output_len`], `None` if the kernel does not fit the padded input.
+#[inline]
+pub(crate) fn checked_output_len(
+    len: usize,
+    kernel: usize,
+    stride: usize,
+    padding: usize,
+    dilation: usize,
+) -> Option<usize> {
+    let span = dilation * (kernel - 1) + 1;
+    Some((len + 2 * padding).checked_sub(span)? / stride + 1)
+}
+
+/// Dimensions of the input shape of a 2d layer.
+#[inline]
+pub(crate) fn input_4d(layer: &str, input_shape: &[usize]) -> Result<[usize; 4]> {
+    input_shape.try_into().map_err(|_| {
+        incompatible(
+            layer,
+            input_shape,
+            "expected a shape (batch_size, channels, height, width)",
+        )
+    })
+}
+
+/// Strided slices of the height and width of a padded input read by the kernel element at the
+/// given offset for every output position.
+#[inline]
+pub(crate) fn window(
+    offset: (usize, usize),
+    out: (usize, usize),
+    stride: (usize, usize),
+) -> (Slice, Slice) {
+    let slice = |offset: usize, out: usize, stride: usize| {
+        Slice::new(
+            offset as isize,
+            Some((offset + stride * (out - 1) + 1) as isize),
+            stride as isize,
+        )
+    };
+    (
+        slice(offset.0, out.0, stride.0),
+        slice(offset.1, out.1, stride.1),
+    )
+}
+
+/// Pads the height and width of an input of shape (batch_size, channels, height, width).
+#[inline]
+pub(crate) fn pad<F: Float>(
+    input: ArrayView4<'_, F>,
+    padding: (usize, usize),
+    value: F,
+) -> Array4<F> {
+    if padding == (0, 0) {
+        return input.to_owned();
+    }
+    let (n, c, h, w) = input.dim();
+    let (ph, pw) = padding;
+    let mut padded = Array4::from_elem((n, c, h + 2 * ph, w + 2 * pw), value);
+    padded
+        .slice_mut(s![.., .., ph..ph + h, pw..pw + w])
+        .assign(&input);
+    padded
+}
+
+/// Inverse of [`pad`], dropping the padded borders.
+#[inline]
+pub(crate) fn unpad<F: Float>(padded: Array4<F>, padding: (usize, usize)) -> Array4<F> {
+    if padding == (0, 0) {
+        return padded;
+    }
+    let (_, _, h, w) = padded.dim();
+    let (ph, pw) = padding;
+    padded.slice(s![.., .., ph..h - ph, pw..w - pw]).to_owned()
+}
+
+/// 2-D convolution over an input of shape `(batch_size, in_channels, height, width)`, computed as
+/// a matrix product of the weight with the unfolded patches of the input (im2col).
+#[derive(Debug)]
+pub struct Conv2d<F = f64> {
+    /// (out_channels, in_channels / groups, kernel_height, kernel_width)
+    weight: ArrayD<F>,
+    /// (out_channels)
+    bias: Option<ArrayD<F>>,
+    options: Conv2dOptions,
+
+    prev_input_shape: Option<Vec<usize>>,
+    /// (in_channels * kernel_height * kernel_width, batch_size * out_height * out_width)
+    prev_columns: Option<Array2<F>>,
+    grad_weight: ArrayD<F>,
+    grad_bias: Option<ArrayD<F>>,
+    weight_requires_grad: bool,
+    bias_requires_grad: bool,
+}
+
+impl<F: Float> Conv2d<F> {
+    #[inline]
+    #[must_use]
+    pub fn new_with_kernel<I: InitParameters<F>>(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: (usize, usize),
+        options: Conv2dOptions,
+        init: I,
+    ) -> Self {
+        let groups = options.groups;
+        assert!(groups > 0, "Groups must be positive");
+        assert_eq!(
+            in_channels % groups,
+            0,
+            "Input channels must be divisible by groups"
+        );
+        assert_eq!(
+            out_channels % groups,
+            0,
+            "Output channels must be divisible by groups"
+        );
+        assert!(
+            kernel_size.0 > 0 && kernel_size.1 > 0,
+            "Kernel size must be positive"
+        );
+        assert!(
+            options.stride.0 > 0 && options.stride.1 > 0,
+            "Stride must be positive"
+        );
+        assert!(
+            options.dilation.0 > 0 && options.dilation.1 > 0,
+            "Dilation must be positive"
+        );
+
+        let shape = [
+            out_channels,
+            in_channels / groups,
+            kernel_size.0,
+            kernel_size.1,
+        ];
+        let weight = init.weight(&shape);
+        let bias = options.bias.then(|| init.bias(&shape));
+        Self {
+            grad_weight: ArrayD::zeros(weight.raw_dim()),
+            grad_bias: bias.as_ref().map(|bias| ArrayD::zeros(bias.raw_dim())),
+            weight,
+            bias,
+            options,
+            prev_input_shape: None,
+            prev_columns: None,
+            weight_requires_grad: true,
+            bias_requires_grad: true,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn new_with_options(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: (usize, usize),
+        options: Conv2dOptions,
+    ) -> Self {
+        Self::new_with_kernel(
+            in_channels,
+            out_channels,
+            kernel_size,
+            options,
+            KaimingNormal,
+        )
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn new(in_channels: usize, out_channels: usize, kernel_size: usize) -> Self {
+        Self::new_with_options(
+            in_channels,
+            out_channels,
+            (kernel_size, kernel_size),
+            Conv2dOptions::default(),
+        )
+    }
+
+    #[inline]
+    pub fn options(&self) -> &Conv2dOptions {
+        &self.options
+    }
+
+    #[inline]
+    fn kernel_size(&self) -> (usize, usize) {
+        (self.weight.shape()[2], self.weight.shape()[3])
+    }
+
+    #[inline]
+    fn output_size(&self, height: usize, width: usize) -> (usize, usize) {
+        let (kh, kw) = self.kernel_size();
+        let Conv2dOptions {
+            stride,
+            padding,
+            dilation,
+            ..
+        } = self.options;
+        (
+            output_len(height, kh, stride.0, padding.0, dilation.0),
+            output_len(width, kw, stride.1, padding.1, dilation.1),
+        )
+    }
+
+    /// (batch_size, channels, height, width) -> (channels * kh * kw, batch_size * oh * ow)
+    fn im2col(&self, input: ArrayView4<'_, F>, out: (usize, usize)) -> Array2<F> {
+        let (n, c, _, _) = input.dim();
+        let (kh, kw) = self.kernel_size();
+        let Conv2dOptions {
+            stride,
+            padding,
+            dilation,
+            ..
+        } = self.options;
+        let padded = pad(input, padding, F::zero());
+
+        let mut columns = Array4::zeros((c, kh * kw, n, out.0 * out.1));
+        for i in 0..kh {
+            for j in 0..kw {
+                let (rows, cols) = window((i * dilation.0, j * dilation.1), out, stride);
+                let patch = padded.slice(s![.., .., rows, cols]);
+                // (n, c, oh, ow) -> (c, n, oh * ow)
+                let patch = patch.permuted_axes([1, 0, 2, 3]);
+                columns.slice_mut(s![.., i * kw + j, .., ..]).assign(
+                    &patch
+                        .as_standard_layout()
+                        .into_shape((c, n, out.0 * out.1))
+                        .unwrap(),
+                );
+            }
+        }
+        columns
+            .into_shape((c * kh * kw, n * out.0 * out.1))
+            .unwrap()
+    }
+
+    /// Inverse of [`Conv2d::im2col`], adding the overlapping patches together.
+    fn col2im(&self, columns: Array2<F>, shape: &[usize], out: (usize, usize)) -> Array4<F> {
+        let (n, c, h, w) = (shape[0], shape[1], shape[2], shape[3]);
+        let (kh, kw) = self.kernel_size();
+        let Conv2dOptions {
+            stride,
+            padding: (ph, pw),
+            dilation,
+            ..
+        } = self.options;
+
+        let columns = columns.into_shape((c, kh * kw, n, out.0, out.1)).unwrap();
+        let mut padded = Array4::zeros((n, c, h + 2 * ph, w + 2 * pw));
+        for i in 0..kh {
+            for j in 0..kw {
+                let (rows, cols) = window((i * dilation.0, j * dilation.1), out, stride);
+                let patch = columns
+                    .slice(s![.., i * kw + j, .., .., ..])
+                    .permuted_axes([1, 0, 2, 3]);
+                let mut target = padded.slice_mut(s![.., .., rows, cols]);
+                target += &patch;
+            }
+        }
+        unpad(padded, (ph, pw))
+    }
+
+    #[inline]
+    fn weight_matrix(&self) -> ArrayView2<'_, F> {
+        let out_channels = self.weight.shape()[0];
+        let size = self.weight.len() / out_channels;
+        self.weight.view().into_shape((out_channels, size)).unwrap()
+    }
+}
+
+impl<F: Float> Module<F> for Conv2d<F> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let input: Array4<F> = input
+            .into_dimensionality()
+            .expect("Conv2d expects an input of shape (batch_size, channels, height, width)");
+        let (n, c, h, w) = input.dim();
+        let groups = self.options.groups;
+        assert_eq!(
+            c,
+            self.weight.shape()[1] * groups,
+            "Conv2d expected {} input channels",
+            self.weight.shape()[1] * groups
+        );
+
+        let out = self.output_size(h, w);
+        let columns = self.im2col(input.view(), out);
+
+        let out_channels = self.weight.shape()[0];
+        let (group_out, group_in) = (out_channels / groups, columns.nrows() / groups);
+        let weight = self.weight_matrix();
+
+        let mut output = Array2::zeros((out_channels, n * out.0 * out.1));
+        for g in 0..groups {
+            let weight = weight.slice(s![g * group_out..(g + 1) * group_out, ..]);
+            let columns = columns.slice(s![g * group_in..(g + 1) * group_in, ..]);
+            output
+                .slice_mut(s![g * group_out..(g + 1) * group_out, ..])
+                .assign(&weight.dot(&columns));
+        }
+
+        // (out_channels, n * oh * ow) -> (n, out_channels, oh, ow)
+        let mut output = output
+            .into_shape((out_channels, n, out.0, out.1))
+            .unwrap()
+            .permuted_axes([1, 0, 2, 3]);
+        if let Some(bias) = &self.bias {
+            let bias = bias.view().into_shape((1, out_channels, 1, 1)).unwrap();
+            output += &bias;
+        }
+
+        self.prev_input_shape = Some(vec![n, c, h, w]);
+        self.prev_columns = Some(columns);
+        output.as_standard_layout().into_owned().into_dyn()
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let shape = saved(&mut self.prev_input_shape, "Conv2d")?;
+        let columns = saved(&mut self.prev_columns, "Conv2d")?;
+        let expected = self.output_shape(&shape)?;
+        if gradient.shape() != expected {
+            return Err(Error::ShapeMismatch {
+                name: "Conv2d gradient".to_string(),
+                expected,
+                found: gradient.shape().to_vec(),
+            });
+        }
+        let gradient: Array4<F> = gradient.into_dimensionality().unwrap();
+        let (n, out_channels, oh, ow) = gradient.dim();
+        let batch_size = F::from_usize(n);
+
+        // (n, out_channels, oh, ow) -> (out_channels, n * oh * ow)
+        let gradient = gradient
+            .permuted_axes([1, 0, 2, 3])
+            .as_standard_layout()
+            .into_shape((out_channels, n * oh * ow))
+            .unwrap()
+            .into_owned();
+
+        if let Some(grad_bias) = self.grad_bias.as_mut().filter(|_| self.bias_requires_grad) {
+            *grad_bias += &(gradient.sum_axis(Axis(1)) / batch_size).into_dyn();
+        }
+
+        let groups = self.options.groups;
+        let (group_out, group_in) = (out_channels / groups, columns.nrows() / groups);
+        let weight = self.weight_matrix();
+
+        let mut grad_weight = Array2::zeros(weight.raw_dim());
+        let mut grad_columns = Array2::zeros(columns.raw_dim());
+        for g in 0..groups {
+            let out_range = g * group_out..(g + 1) * group_out;
+            let in_range = g * group_in..(g + 1) * group_in;
+            let gradient = gradient.slice(s![out_range.clone(), ..]);
+
+            if self.weight_requires_grad {
+                grad_weight
+                    .slice_mut(s![out_range.clone(), ..])
+                    .assign(&gradient.dot(&columns.slice(s![in_range.clone(), ..]).t()));
+            }
+            grad_columns
+                .slice_mut(s![in_range, ..])
+                .assign(&weight.slice(s![out_range, ..]).t().dot(&gradient));
+        }
+
+        if self.weight_requires_grad {
+            self.grad_weight += &(grad_weight / batch_size)
+                .into_shape(self.weight.raw_dim())
+                .unwrap();
+        }
+        Ok(self.col2im(grad_columns, &shape, (oh, ow)).into_dyn())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        let params = Parameters::new(2).add_with_requires_grad(
+            "weight",
+            &mut self.weight,
+            &mut self.grad_weight,
+            &mut self.weight_requires_grad,
+        );
+
+        match (self.bias.as_mut(), self.grad_bias.as_mut()) {
+            (Some(bias), Some(grad_bias)) => {
+                params.add_with_requires_grad("bias", bias, grad_bias, &mut self.bias_requires_grad)
+            }
+            _ => params,
+        }
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        let [n, c, h, w] = input_4d("Conv2d", input_shape)?;
+        let in_channels = self.weight.shape()[1] * self.options.groups;
+        if c != in_channels {
+            let reason = format!("expected {in_channels} input channels, found {c}");
+            return Err(incompatible("Conv2d", input_shape, reason));
+        }
+
+        let (kh, kw) = self.kernel_size();
+        let Conv2dOptions {
+            stride,
+            padding,
+            dilation,
+            ..
+        } = self.options;
+        let (oh, ow) = checked_output_len(h, kh, stride.0, padding.0, dilation.0)
+            .zip(checked_output_len(w, kw, stride.1, padding.1, dilation.1))
+            .ok_or_else(|| {
+                let reason = format!(
+                    "kernel of size {:?} does not fit the padded input",
+                    (kh, kw)
+                );
+                incompatible("Conv2d", input_shape, reason)
+            })?;
+        Ok(vec![n, self.weight.shape()[0], oh, ow])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+    use crate::module::init::Normal;
+
+    /// Direct implementation of the convolution used as reference
+    fn naive(input: &Array4<f64>, weight: &ArrayD<f64>, options: Conv2dOptions) -> Array4<f64> {
+        let (n, c, h, w) = input.dim();
+        let (o, cg, kh, kw) = (
+            weight.shape()[0],
+            weight.shape()[1],
+            weight.shape()[2],
+            weight.shape()[3],
+        );
+        let Conv2dOptions {
+            stride,
+            padding,
+            dilation,
+            groups,
+            ..
+        } = options;
+        let oh = output_len(h, kh, stride.0, padding.0, dilation.0);
+        let ow = output_len(w, kw, stride.1, padding.1, dilation.1);
+        let group_out = o / groups;
+        assert_eq!(c, cg * groups);
+
+        let mut output = Array4::zeros((n, o, oh, ow));
+        for ((b, oc, y, x), value) in output.indexed_iter_mut() {
+            let g = oc / group_out;
+            for ic in 0..cg {
+                for i in 0..kh {
+                    for j in 0..kw {
+                        let row = (y * stride.0 + i * dilation.0) as isize - padding.0 as isize;
+                        let col = (x * stride.1 + j * dilation.1) as isize - padding.1 as isize;
+                        if row < 0 || col < 0 || row >= h as isize || col >= w as isize {
+                            continue;
+                        }
+                        *value += weight[[oc, ic, i, j]]
+                            * input[[b, g * cg + ic, row as usize, col as usize]];
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    fn input(shape: (usize, usize, usize, usize)) -> Array4<f64> {
+        let len = shape.0 * shape.1 * shape.2 * shape.3;
+        Array::linspace(-1.0, 1.0, len)
+            .mapv(|x: f64| (7.0 * x).sin())
+            .into_shape(shape)
+            .unwrap()
+    }
+
+    fn options() -> Vec<Conv2dOptions> {
+        vec![
+            Conv2dOptions::default(),
+            Conv2dOptions::default().stride(2),
+            Conv2dOptions::default().padding(1),
+            Conv2dOptions::default().dilation(2).padding(2),
+            Conv2dOptions {
+                stride: (2, 1),
+                padding: (1, 0),
+                ..Default::default()
+            },
+            Conv2dOptions::default().groups(2).bias(false),
+        ]
+    }
+
+    #[test]
+    fn output_shape() {
+        for options in options() {
+            let mut module = Conv2d::new_with_options(4, 2, (3, 2), options);
+            let result = module.forward(input((2, 4, 5, 4)).into_dyn());
+            let shape = module.output_shape(&[2, 4, 5, 4]).unwrap();
+            assert_eq!(result.shape(), shape);
+        }
+
+        let module = Conv2d::<f64>::new(3, 8, 5);
+        match module.output_shape(&[2, 1, 28, 28]) {
+            Err(crate::Error::IncompatibleShape { layer, reason, .. }) => {
+                assert_eq!("Conv2d", layer);
+                assert_eq!("expected 3 input channels, found 1", reason);
+            }
+            result => panic!("Expected an incompatible shape, found {result:?}"),
+        }
+        assert!(module.output_shape(&[2, 3, 4, 28]).is_err());
+        assert!(module.output_shape(&[3, 28, 28]).is_err());
+    }
+
+    #[test]
+    fn forward() {
+        for options in options() {
+            let mut module =
+                Conv2d::new_with_kernel(4, 2, (3, 2), options, Normal::new_std(1.0).unwrap());
+            let data = input((2, 4, 5, 4));
+
+            let result = module.forward(data.clone().into_dyn());
+            let mut expected = naive(&data, &module.weight, options);
+            if let Some(bias) = &module.bias {
+                expected += &bias.view().into_shape((1, 2, 1, 1)).unwrap();
+            }
+            let expected = expected.into_dyn();
+            assert_array_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn backward() {
+        // Loss is the sum of the output weighted by a fixed gradient
+        const EPSILON: f64 = 1e-6;
+        for options in options() {
+            let mut module =
+                Conv2d::new_with_kernel(4, 2, (3, 2), options, Normal::new_std(1.0).unwrap());
+            let data = input((2, 4, 5, 4));
+            let output = module.forward(data.clone().into_dyn());
+            let shape = output.shape();
+            let gradient = input((shape[0], shape[1], shape[2], shape[3])).mapv(|x| x + 0.5);
+            let grad_input = module.backward(gradient.clone().into_dyn()).unwrap();
+            let loss = |module: &Conv2d, data: &Array4<f64>| {
+                (naive(data, &module.weight, options) * &gradient).sum()
+            };
+
+            // Input gradient
+            let mut expected = Array4::zeros(data.raw_dim());
+            for (index, value) in expected.indexed_iter_mut() {
+                let mut perturbed = data.clone();
+                perturbed[index] += EPSILON;
+                *value = (loss(&module, &perturbed) - loss(&module, &data)) / EPSILON;
+            }
+            let expected = expected.into_dyn();
+            assert_array_eq!(grad_input, expected, 1e-4);
+
+            // Weight gradient is averaged over the batch
+            let mut expected = ArrayD::zeros(module.weight.raw_dim());
+            let base = loss(&module, &data);
+            for index in ndarray::indices(module.weight.raw_dim()) {
+                module.weight[&index] += EPSILON;
+                expected[&index] = (loss(&module, &data) - base) / EPSILON / 2.0;
+                module.weight[&index] -= EPSILON;
+            }
+            assert_array_eq!(module.grad_weight.clone(), expected, 1e-4);
+
+            if module.bias.is_some() {
+                let expected = gradient
+                    .sum_axis(Axis(3))
+                    .sum_axis(Axis(2))
+                    .sum_axis(Axis(0))
+                    / 2.0;
+                let expected = expected.into_dyn();
+                assert_array_eq!(module.grad_bias.clone().unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn backward_wrong_gradient() {
+        let mut module = Conv2d::<f64>::new(3, 8, 5);
+        module.forward(ArrayD::zeros(vec![2, 3, 10, 10]));
+        let result = module.backward(ArrayD::zeros(vec![2, 8, 6]));
+        assert!(matches!(result, Err(Error::ShapeMismatch { .. })));
+    }
+
+    #[test]
+    #[should_panic(expected = "Kernel size must be positive")]
+    fn empty_kernel() {
+        let _ = Conv2d::<f64>::new_with_options(3, 8, (0, 3), Conv2dOptions::default());
+    }
+
+    #[test]
+    fn parameters() {
+        let mut module = Conv2d::<f64>::new(3, 8, 5);
+        assert_eq!(&[8, 3, 5, 5], module.weight.shape());
+
+        module.forward(ArrayD::zeros(vec![2, 3, 10, 10]));
+        module.backward(ArrayD::zeros(vec![2, 8, 6, 6])).unwrap();
+        let shapes: Vec<_> = module
+            .parameters()
+            .iter()
+            .map(|p| p.parm.shape().to_vec())
+            .collect();
+        assert_eq!(vec![vec![8, 3, 5, 5], vec![8]], shapes);
+    }
+
+    #[test]
+    fn fan_in_uses_kernel_volume() {
+        let module = Conv2d::<f64>::new_with_options(64, 64, (3, 3), Conv2dOptions::default());
+        // Kaiming: std = 1 / sqrt(64 * 3 * 3) = 1 / 24
+        let std = module.weight.std(0.0);
+        assert!((std - 1.0 / 24.0).abs() < 5e-3, "std={std}");
+    }
+}
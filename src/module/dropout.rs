@@ -0,0 +1,265 @@
+use crate::module::conv::input_4d;
+use crate::module::{saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Random masks shared by the dropout modules, only applied in training mode.
+#[derive(Debug, Clone)]
+struct Dropper<F> {
+    p: f64,
+    training: bool,
+    rng: ChaCha8Rng,
+    /// Mask of the last forward pass scaled like the output, `None` in evaluation mode
+    prev_mask: Option<Option<ArrayD<F>>>,
+}
+
+impl<F: Float> Dropper<F> {
+    #[inline]
+    fn new(p: f64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&p),
+            "Dropout probability must be in [0, 1)"
+        );
+        Self {
+            p,
+            training: true,
+            rng: ChaCha8Rng::seed_from_u64(rand::random()),
+            prev_mask: None,
+        }
+    }
+
+    /// Mask of an array of the given shape, one for the kept elements and zero for the dropped.
+    #[inline]
+    fn mask(&mut self, shape: &[usize]) -> ArrayD<F> {
+        let Self { p, rng, .. } = self;
+        ArrayD::from_shape_simple_fn(shape, || match rng.gen::<f64>() < *p {
+            true => F::zero(),
+            false => F::one(),
+        })
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>, module: &str) -> Result<ArrayD<F>> {
+        Ok(match saved(&mut self.prev_mask, module)? {
+            Some(mask) => gradient * mask,
+            None => gradient,
+        })
+    }
+}
+
+/// Implements a dropout module around a [`Dropper`], from its inherent `dropout` method giving
+/// the output in training mode and the mask to save, and `input_shape` checking the input.
+macro_rules! dropout {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        pub struct $name<F = f64>(Dropper<F>);
+
+        impl<F: Float> $name<F> {
+            #[inline]
+            #[must_use]
+            pub fn new(p: f64) -> Self {
+                Self(Dropper::new(p))
+            }
+
+            /// Seeds the masks, which then only depend on the seed and the shapes of the previous
+            /// inputs.
+            #[inline]
+            #[must_use]
+            pub fn seed(mut self, seed: u64) -> Self {
+                self.0.rng = ChaCha8Rng::seed_from_u64(seed);
+                self
+            }
+        }
+
+        impl<F: Float> Module<F> for $name<F> {
+            fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+                if let Err(error) = Self::input_shape(input.shape()) {
+                    panic!("{error}");
+                }
+                if !self.0.training {
+                    self.0.prev_mask = Some(None);
+                    return input;
+                }
+
+                let (output, mask) = self.dropout(input);
+                self.0.prev_mask = Some(Some(mask));
+                output
+            }
+
+            #[inline]
+            fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+                self.0.backward(gradient, stringify!($name))
+            }
+
+            #[inline]
+            fn parameters(&mut self) -> Parameters<'_, F> {
+                Parameters::new(0)
+            }
+
+            #[inline]
+            fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+                Self::input_shape(input_shape)
+            }
+
+            #[inline]
+            fn train(&mut self) {
+                self.0.training = true;
+            }
+
+            #[inline]
+            fn eval(&mut self) {
+                self.0.training = false;
+            }
+        }
+    };
+}
+
+dropout!(
+    /// Zeroes every element with probability `p` in training mode, scaling the others by
+    /// `1 / (1 - p)` so that the expected output is the input. Identity in evaluation mode.
+    Dropout
+);
+
+impl<F: Float> Dropout<F> {
+    fn dropout(&mut self, input: ArrayD<F>) -> (ArrayD<F>, ArrayD<F>) {
+        let scale = F::from_f64(1.0 / (1.0 - self.0.p));
+        let mask = self.0.mask(input.shape()) * scale;
+        (input * &mask, mask)
+    }
+
+    #[inline]
+    fn input_shape(input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+dropout!(
+    /// Zeroes whole channels of an input of shape `(batch_size, channels, height, width)` with
+    /// probability `p` in training mode, scaling the others by `1 / (1 - p)`. Identity in
+    /// evaluation mode.
+    Dropout2d
+);
+
+impl<F: Float> Dropout2d<F> {
+    fn dropout(&mut self, input: ArrayD<F>) -> (ArrayD<F>, ArrayD<F>) {
+        let (n, c) = (input.shape()[0], input.shape()[1]);
+        let scale = F::from_f64(1.0 / (1.0 - self.0.p));
+        let mask = self.0.mask(&[n, c, 1, 1]) * scale;
+        (input * &mask, mask)
+    }
+
+    #[inline]
+    fn input_shape(input_shape: &[usize]) -> Result<Vec<usize>> {
+        input_4d("Dropout2d", input_shape).map(Vec::from)
+    }
+}
+
+/// Negative saturation value of SELU, `-scale * alpha`.
+const ALPHA_PRIME: f64 = -1.758_099_340_847_376_6;
+
+dropout!(
+    /// Dropout for self-normalizing networks using SELU: dropped elements are set to the negative
+    /// saturation value of SELU, and the output is scaled and shifted so that an input of zero
+    /// mean and unit variance keeps them. Identity in evaluation mode.
+    AlphaDropout
+);
+
+impl<F: Float> AlphaDropout<F> {
+    fn dropout(&mut self, input: ArrayD<F>) -> (ArrayD<F>, ArrayD<F>) {
+        let p = self.0.p;
+        let a = ((1.0 - p) * (1.0 + p * ALPHA_PRIME * ALPHA_PRIME)).powf(-0.5);
+        let b = -a * ALPHA_PRIME * p;
+        let (a, b, alpha) = (F::from_f64(a), F::from_f64(b), F::from_f64(ALPHA_PRIME));
+
+        let mask = self.0.mask(input.shape());
+        let mut output = input;
+        azip!((x in &mut output, &m in &mask) {
+            *x = a * (*x * m + alpha * (F::one() - m)) + b;
+        });
+        (output, mask * a)
+    }
+
+    #[inline]
+    fn input_shape(input_shape: &[usize]) -> Result<Vec<usize>> {
+        Ok(input_shape.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+    use crate::module::{Linear, Sequential};
+    use crate::sequential;
+
+    #[test]
+    fn dropout() {
+        let mut module = Dropout::new(0.5).seed(3);
+        let data = ArrayD::from_elem(vec![100, 100], 3.0);
+        let output = module.forward(data.clone());
+        assert!(output.iter().all(|&x| x == 0.0 || x == 6.0));
+        let kept = output.iter().filter(|&&x| x != 0.0).count();
+        assert!((4_500..5_500).contains(&kept), "{kept}");
+
+        // Gradients go through the same mask
+        let result = module.backward(ArrayD::ones(vec![100, 100])).unwrap();
+        let expected = &output / 3.0;
+        assert_array_eq!(result, expected);
+
+        // Same seed, same masks
+        let mut other = Dropout::new(0.5).seed(3);
+        assert_eq!(output, other.forward(data.clone()));
+        assert_ne!(output, other.forward(data.clone()));
+
+        module.eval();
+        assert_eq!(data, module.forward(data.clone()));
+        assert_eq!(data, module.backward(data.clone()).unwrap());
+    }
+
+    #[test]
+    fn dropout_2d() {
+        let mut module = Dropout2d::new(0.5).seed(1);
+        let data = ArrayD::from_elem(vec![4, 8, 3, 3], 1.0);
+        let output = module.forward(data);
+        for channel in output
+            .lanes(Axis(2))
+            .into_iter()
+            .collect::<Vec<_>>()
+            .chunks(3)
+        {
+            let sum: f64 = channel.iter().map(|lane| lane.sum()).sum();
+            assert!(sum == 0.0 || sum == 18.0, "{sum}");
+        }
+        assert!(Module::<f64>::output_shape(&module, &[4, 8, 3]).is_err());
+    }
+
+    #[test]
+    fn alpha_dropout() {
+        let mut module = AlphaDropout::new(0.2).seed(5);
+        let data = f64::random_normal(&[200_000], 0.0, 1.0);
+        let output = module.forward(data);
+        let mean = output.mean().unwrap();
+        let var = output.mapv(|x| (x - mean).powi(2)).mean().unwrap();
+        assert!(mean.abs() < 0.02, "{mean}");
+        assert!((var - 1.0).abs() < 0.03, "{var}");
+
+        module.eval();
+        let data = array![[1.0, -2.0]].into_dyn();
+        assert_eq!(data, module.forward(data.clone()));
+    }
+
+    #[test]
+    fn sequential_mode() {
+        let mut module: Sequential = sequential!(Linear::new(4, 4), Dropout::new(0.9).seed(0));
+        let data = ArrayD::ones(vec![8, 4]);
+        module.eval();
+        let expected = module.forward(data.clone());
+        assert_eq!(expected, module.forward(data.clone()));
+
+        module.train();
+        assert_ne!(expected, module.forward(data));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::module::{incompatible, saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+use std::marker::PhantomData;
+
+/// Collapses every dimension but the batch one: (batch_size, *) -> (batch_size, prod(*)), e.g. to
+/// feed the output of a [`Conv2d`](crate::module::Conv2d) to a [`Linear`](crate::module::Linear).
+#[derive(Debug)]
+pub struct Flatten<F = f64> {
+    prev_shape: Option<Vec<usize>>,
+    float: PhantomData<F>,
+}
+
+impl<F> Flatten<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            prev_shape: None,
+            float: PhantomData,
+        }
+    }
+}
+
+impl<F> Default for Flatten<F> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Float> Module<F> for Flatten<F> {
+    #[inline]
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let shape = input.shape().to_vec();
+        let n = shape[0];
+        let size = shape[1..].iter().product::<usize>();
+        self.prev_shape = Some(shape);
+
+        input
+            .as_standard_layout()
+            .into_owned()
+            .into_shape(vec![n, size])
+            .unwrap()
+    }
+
+    #[inline]
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let shape = saved(&mut self.prev_shape, "Flatten")?;
+        Ok(gradient
+            .as_standard_layout()
+            .into_owned()
+            .into_shape(shape)
+            .unwrap())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        match input_shape {
+            [n, rest @ ..] => Ok(vec![*n, rest.iter().product()]),
+            [] => Err(incompatible(
+                "Flatten",
+                input_shape,
+                "expected a batch dimension",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forward_backward() {
+        let mut module = Flatten::new();
+        let data = Array::range(0.0, 12.0, 1.0)
+            .into_shape((2, 3, 2))
+            .unwrap()
+            .into_dyn();
+        let result = module.forward(data.clone());
+        assert_eq!(&[2, 6], result.shape());
+
+        let result = module.backward(result).unwrap();
+        crate::assert_array_eq!(result, data);
+    }
+}
@@ -1,15 +1,15 @@
-use crate::module::init::InitParameters;
+use crate::module::init::{fan_in_out, InitParameters};
+use crate::Float;
 use ndarray::prelude::*;
-use ndarray_rand::rand_distr::Normal;
-use ndarray_rand::RandomExt;
 
 #[derive(Debug, Default)]
 pub struct KaimingNormal;
 
-impl InitParameters for KaimingNormal {
+impl<F: Float> InitParameters<F> for KaimingNormal {
     #[inline]
-    fn weight(&self, input_size: usize, output_size: usize) -> Array2<f64> {
-        let sigma = 1.0 / f64::sqrt(input_size as f64);
-        Array::random((output_size, input_size), Normal::new(0.0, sigma).unwrap())
+    fn weight(&self, shape: &[usize]) -> ArrayD<F> {
+        let (fan_in, _) = fan_in_out(shape);
+        let sigma = 1.0 / f64::sqrt(fan_in as f64);
+        F::random_normal(shape, 0.0, sigma)
     }
 }
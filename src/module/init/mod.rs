@@ -1,3 +1,4 @@
+use crate::Float;
 use ndarray::prelude::*;
 
 mod he;
@@ -8,11 +9,27 @@ pub use he::KaimingNormal;
 pub use sigma::Normal;
 pub use xavier::XavierNormal;
 
-pub trait InitParameters {
-    fn weight(&self, input_size: usize, output_size: usize) -> Array2<f64>;
+/// Number of inputs and outputs connected to each unit of a weight of shape
+/// `(output_size, input_size, *kernel_size)`.
+#[inline]
+pub fn fan_in_out(shape: &[usize]) -> (usize, usize) {
+    match shape {
+        [] => (1, 1),
+        [size] => (*size, *size),
+        [output_size, input_size, kernel_size @ ..] => {
+            let receptive_field: usize = kernel_size.iter().product();
+            (input_size * receptive_field, output_size * receptive_field)
+        }
+    }
+}
+
+pub trait InitParameters<F: Float = f64> {
+    /// Weight of shape `(output_size, input_size, *kernel_size)`
+    fn weight(&self, shape: &[usize]) -> ArrayD<F>;
 
+    /// Bias of shape `(output_size)` for a weight of the given shape
     #[inline]
-    fn bias(&self, _input_size: usize, output_size: usize) -> Array2<f64> {
-        Array2::zeros((1, output_size))
+    fn bias(&self, shape: &[usize]) -> ArrayD<F> {
+        ArrayD::zeros(IxDyn(&shape[..1]))
     }
 }
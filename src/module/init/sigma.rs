@@ -1,7 +1,6 @@
 use crate::module::init::InitParameters;
+use crate::{Error, Float, Result};
 use ndarray::prelude::*;
-use ndarray_rand::rand_distr::Normal as NormalDist;
-use ndarray_rand::RandomExt;
 
 #[derive(Debug, Default)]
 pub struct Normal {
@@ -10,28 +9,40 @@ pub struct Normal {
 }
 
 impl Normal {
+    /// Fails with [`Error::InvalidArgument`] if the standard deviation is negative or not finite.
     #[inline]
-    #[must_use]
-    pub fn new(mean: f64, std: f64) -> Option<Self> {
-        match std.is_finite() {
-            true => Some(Self { mean, std }),
-            false => None,
+    pub fn new(mean: f64, std: f64) -> Result<Self> {
+        match std.is_finite() && std >= 0.0 {
+            true => Ok(Self { mean, std }),
+            false => Err(Error::InvalidArgument(format!(
+                "Standard deviation must be finite and non-negative, got {std}"
+            ))),
         }
     }
 
     #[inline]
-    #[must_use]
-    pub fn new_std(std: f64) -> Option<Self> {
+    pub fn new_std(std: f64) -> Result<Self> {
         Self::new(0.0, std)
     }
 }
 
-impl InitParameters for Normal {
+impl<F: Float> InitParameters<F> for Normal {
     #[inline]
-    fn weight(&self, input_size: usize, output_size: usize) -> Array2<f64> {
-        Array::random(
-            (output_size, input_size),
-            NormalDist::new(self.mean, self.std).unwrap(),
-        )
+    fn weight(&self, shape: &[usize]) -> ArrayD<F> {
+        F::random_normal(shape, self.mean, self.std)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_std() {
+        for std in [-1.0, f64::NAN, f64::INFINITY] {
+            let result = Normal::new(0.0, std);
+            assert!(matches!(result, Err(Error::InvalidArgument(_))), "{std}");
+        }
+        assert!(Normal::new_std(0.0).is_ok());
     }
 }
@@ -1,15 +1,15 @@
-use crate::module::init::InitParameters;
+use crate::module::init::{fan_in_out, InitParameters};
+use crate::Float;
 use ndarray::prelude::*;
-use ndarray_rand::rand_distr::Normal;
-use ndarray_rand::RandomExt;
 
 #[derive(Debug, Default)]
 pub struct XavierNormal;
 
-impl InitParameters for XavierNormal {
+impl<F: Float> InitParameters<F> for XavierNormal {
     #[inline]
-    fn weight(&self, input_size: usize, output_size: usize) -> Array2<f64> {
-        let sigma = f64::sqrt(2.0 / (input_size as f64 + output_size as f64));
-        Array::random((output_size, input_size), Normal::new(0.0, sigma).unwrap())
+    fn weight(&self, shape: &[usize]) -> ArrayD<F> {
+        let (fan_in, fan_out) = fan_in_out(shape);
+        let sigma = f64::sqrt(2.0 / (fan_in as f64 + fan_out as f64));
+        F::random_normal(shape, 0.0, sigma)
     }
 }
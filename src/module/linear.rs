@@ -1,79 +1,105 @@
+use crate::autograd::Tensor;
 use crate::module::init::{InitParameters, KaimingNormal};
-use crate::module::Module;
+use crate::module::{incompatible, Autograd, AutogradModule, Module};
+use crate::{Float, Result};
 use ndarray::prelude::*;
 
 use super::Parameters;
 
+/// Forward pass of [`Linear`], whose backward pass is derived by [`Autograd`].
 #[derive(Debug)]
-pub struct Linear {
-    weight: Array2<f64>,
-    bias: Option<Array2<f64>>,
+struct LinearTensors<F: Float> {
+    /// (output_size, input_size)
+    weight: Tensor<F>,
+    /// (output_size)
+    bias: Tensor<F>,
+}
+
+impl<F: Float> AutogradModule<F> for LinearTensors<F> {
+    fn forward(&mut self, input: &Tensor<F>) -> Tensor<F> {
+        let weight_shape = self.weight.shape();
+        let (output_size, input_size) = (weight_shape[0], weight_shape[1]);
+
+        // Collapses all the leading dimensions: (*, size) -> (prod(*), size)
+        let mut shape = input.shape();
+        let rows = shape.iter().product::<usize>() / input_size.max(1);
+        let x = &input.reshape(&[rows, input_size]).matmul(&self.weight.t()) + &self.bias;
+
+        *shape.last_mut().unwrap() = output_size;
+        x.reshape(&shape)
+    }
 
-    prev_input: Option<Array2<f64>>,
-    grad_weight: Option<Array2<f64>>,
-    grad_bias: Option<Array2<f64>>,
+    fn tensors(&mut self) -> Vec<(&str, &mut Tensor<F>)> {
+        vec![("weight", &mut self.weight), ("bias", &mut self.bias)]
+    }
 }
 
-impl Linear {
+/// Applies `x W^T + b` over the last axis of the input, so any number of leading dimensions is
+/// supported: `(*, input_size) -> (*, output_size)`.
+#[derive(Debug)]
+pub struct Linear<F: Float = f64> {
+    module: Autograd<LinearTensors<F>, F>,
+}
+
+impl<F: Float> Linear<F> {
     #[inline]
     #[must_use]
-    pub fn new_with_kernel<I: InitParameters>(
+    pub fn new_with_kernel<I: InitParameters<F>>(
         input_size: usize,
         output_size: usize,
         init: I,
-    ) -> Linear {
+    ) -> Self {
+        let shape = [output_size, input_size];
+        let tensors = LinearTensors {
+            weight: Tensor::new(init.weight(&shape)),
+            bias: Tensor::new(init.bias(&shape)),
+        };
         Linear {
-            weight: init.weight(input_size, output_size),
-            bias: Some(init.bias(input_size, output_size)),
-            prev_input: None,
-            grad_weight: None,
-            grad_bias: None,
+            module: Autograd::new(tensors),
         }
     }
 
     #[inline]
     #[must_use]
-    pub fn new(input_size: usize, output_size: usize) -> Linear {
-        Linear::new_with_kernel(input_size, output_size, KaimingNormal)
+    pub fn new(input_size: usize, output_size: usize) -> Self {
+        Self::new_with_kernel(input_size, output_size, KaimingNormal)
     }
 }
 
-impl Module for Linear {
+impl<F: Float> Module<F> for Linear<F> {
     #[inline]
-    fn forward(&mut self, input: Array2<f64>) -> Array2<f64> {
-        let mut x = input.dot(&self.weight.t());
-
-        // Debug assert to validate proper shape
-        let batch_size = input.shape()[0];
-        let output_size = self.weight.shape()[0];
-        debug_assert_eq!(x.shape(), &[batch_size, output_size]);
-
-        if let Some(bias) = &self.bias {
-            x += bias;
-        }
-
-        self.prev_input = Some(input);
-        x
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        self.module.forward(input)
     }
 
     #[inline]
-    fn backward(&mut self, gradient: Array2<f64>) -> Array2<f64> {
-        let prev_input = self.prev_input.take().unwrap();
-        let n = prev_input.nrows() as f64;
-        self.grad_weight = Some(gradient.t().dot(&prev_input) / n);
-
-        if self.bias.is_some() {
-            self.grad_bias = Some((gradient.sum_axis(Axis(0)) / n).insert_axis(Axis(0)));
-        }
-        gradient.dot(&self.weight)
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        self.module.backward(gradient)
     }
 
-    fn parameters(&mut self) -> Parameters<'_> {
-        let params = Parameters::new(2).add(&mut self.weight, self.grad_weight.as_mut().unwrap());
+    #[inline]
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        self.module.parameters()
+    }
 
-        match self.bias.as_mut() {
-            Some(bias) => params.add(bias, self.grad_bias.as_mut().unwrap()),
-            None => params,
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        let weight_shape = self.module.inner().weight.shape();
+        let (output_size, input_size) = (weight_shape[0], weight_shape[1]);
+        match input_shape {
+            [_, .., size] if *size == input_size => {
+                let mut shape = input_shape.to_vec();
+                *shape.last_mut().unwrap() = output_size;
+                Ok(shape)
+            }
+            [_, .., size] => {
+                let reason = format!("expected {input_size} input features, found {size}");
+                Err(incompatible("Linear", input_shape, reason))
+            }
+            _ => Err(incompatible(
+                "Linear",
+                input_shape,
+                "expected a shape (batch_size, *, input_size)",
+            )),
         }
     }
 }
@@ -85,21 +111,47 @@ mod tests {
     #[derive(Debug)]
     struct FixedInit;
 
-    impl InitParameters for FixedInit {
-        fn weight(&self, _input_size: usize, _output_size: usize) -> Array2<f64> {
-            array![[1.0, 0.5]]
+    impl<F: Float> InitParameters<F> for FixedInit {
+        fn weight(&self, _shape: &[usize]) -> ArrayD<F> {
+            array![[1.0, 0.5]].mapv(F::from_f64).into_dyn()
         }
-        fn bias(&self, _input_size: usize, _output_size: usize) -> Array2<f64> {
-            array![[10.0]]
+        fn bias(&self, _shape: &[usize]) -> ArrayD<F> {
+            array![10.0].mapv(F::from_f64).into_dyn()
         }
     }
 
+    /// Values and gradients of the weight and bias of `module`.
+    fn parameters(module: &mut Linear) -> Vec<(ArrayD<f64>, ArrayD<f64>)> {
+        let parms = module.parameters().iter();
+        parms.map(|p| (p.parm.clone(), p.grad.clone())).collect()
+    }
+
     #[test]
     fn forward() {
         let mut module = Linear::new_with_kernel(2, 1, FixedInit);
-        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]];
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0]].into_dyn();
         let result = module.forward(data.clone());
-        let expected = array![[12.0], [11.0], [2.0]];
+        let expected = array![[12.0], [11.0], [2.0]].into_dyn();
+
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn forward_f32() {
+        let mut module = Linear::new_with_kernel(2, 1, FixedInit);
+        let data = array![[1.0f32, 2.0], [3.0, -4.0], [-5.0, -6.0]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[12.0f32], [11.0], [2.0]].into_dyn();
+
+        crate::assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn forward_leading_dimensions() {
+        let mut module = Linear::new_with_kernel(2, 1, FixedInit);
+        let data = array![[[1.0, 2.0], [3.0, -4.0]], [[-5.0, -6.0], [0.0, 0.0]]].into_dyn();
+        let result = module.forward(data);
+        let expected = array![[[12.0], [11.0]], [[2.0], [10.0]]].into_dyn();
 
         crate::assert_array_eq!(result, expected);
     }
@@ -107,26 +159,56 @@ mod tests {
     #[test]
     fn backward() {
         let mut module = Linear::new_with_kernel(2, 1, FixedInit);
-        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0], [-7.0, 8.0]];
+        let data = array![[1.0, 2.0], [3.0, -4.0], [-5.0, -6.0], [-7.0, 8.0]].into_dyn();
         module.forward(data.clone());
-        let result = module.backward(Array::ones((4, 1)));
+        let result = module.backward(ArrayD::ones(vec![4, 1])).unwrap();
 
-        assert_eq!(
-            module.grad_weight.as_ref().unwrap().shape(),
-            module.weight.shape()
-        );
+        let parms = parameters(&mut module);
+        let [(weight, grad_weight), (bias, grad_bias)]: [_; 2] = parms.try_into().unwrap();
+        assert_eq!(grad_weight.shape(), weight.shape());
+        assert_eq!(grad_bias.shape(), bias.shape());
 
-        assert_eq!(
-            module.grad_bias.as_ref().unwrap().shape(),
-            module.bias.unwrap().shape()
-        );
+        let expected_grad_w = array![[-2.0, 0.0]].into_dyn();
+        let expected_grad_b = array![1.0].into_dyn();
+        let expected_grad = array![[1.0, 0.5], [1.0, 0.5], [1.0, 0.5], [1.0, 0.5]].into_dyn();
 
-        let expected_grad_w = array![[-2.0, 0.0]];
-        let expected_grad_b = array![[1.0]];
-        let expected_grad = array![[1.0, 0.5], [1.0, 0.5], [1.0, 0.5], [1.0, 0.5]];
+        crate::assert_array_eq!(grad_weight, expected_grad_w);
+        crate::assert_array_eq!(grad_bias, expected_grad_b);
+        crate::assert_array_eq!(result, expected_grad);
+    }
 
-        crate::assert_array_eq!(module.grad_weight.clone().unwrap(), expected_grad_w);
-        crate::assert_array_eq!(module.grad_bias.clone().unwrap(), expected_grad_b);
+    #[test]
+    fn backward_leading_dimensions() {
+        let mut module = Linear::new_with_kernel(2, 1, FixedInit);
+        let data = array![[[1.0, 2.0], [3.0, -4.0]], [[-5.0, -6.0], [-7.0, 8.0]]].into_dyn();
+        module.forward(data);
+        let result = module.backward(ArrayD::ones(vec![2, 2, 1])).unwrap();
+
+        // Gradients are summed over the sequence and averaged over the batch
+        let expected_grad_w = array![[-4.0, 0.0]].into_dyn();
+        let expected_grad_b = array![2.0].into_dyn();
+        let expected_grad = array![[[1.0, 0.5], [1.0, 0.5]], [[1.0, 0.5], [1.0, 0.5]]].into_dyn();
+
+        let parms = parameters(&mut module);
+        let (grad_weight, grad_bias) = (parms[0].1.clone(), parms[1].1.clone());
+        crate::assert_array_eq!(grad_weight, expected_grad_w);
+        crate::assert_array_eq!(grad_bias, expected_grad_b);
         crate::assert_array_eq!(result, expected_grad);
     }
+
+    #[test]
+    fn backward_before_forward() {
+        let mut module = Linear::<f64>::new(2, 1);
+        let result = module.backward(ArrayD::ones(vec![4, 1]));
+        assert!(matches!(result, Err(crate::Error::State(_))));
+
+        // Gradients exist, and are zero, before any backward pass
+        let grads: Vec<_> = module.parameters().iter().map(|p| p.grad.sum()).collect();
+        assert_eq!(vec![0.0, 0.0], grads);
+
+        module.forward(ArrayD::ones(vec![4, 2]));
+        module.backward(ArrayD::ones(vec![4, 1])).unwrap();
+        module.zero_grad();
+        assert!(module.parameters().iter().all(|p| p.grad.sum() == 0.0));
+    }
 }
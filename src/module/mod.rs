@@ -1,52 +1,412 @@
+use crate::{Error, Float, Result};
 use ndarray::prelude::*;
 
 pub mod activation;
+pub(crate) mod autograd;
+pub(crate) mod conv;
+pub(crate) mod dropout;
+pub(crate) mod flatten;
 pub mod init;
 pub(crate) mod linear;
+pub(crate) mod norm;
+pub(crate) mod pool;
 pub(crate) mod safe_module;
+#[cfg(feature = "safetensors")]
+mod safetensors;
 pub(crate) mod sequential;
+pub(crate) mod state_dict;
+pub(crate) mod summary;
 
-pub use activation::Identity;
-pub use activation::ReLU;
-pub use activation::Softmax;
+pub use activation::{
+    Identity, LeakyReLU, LogSoftmax, Mish, PReLU, ReLU, SiLU, Sigmoid, Softmax, Softplus, Tanh,
+    ELU, GELU,
+};
+pub use autograd::{Autograd, AutogradModule};
+pub use conv::{Conv2d, Conv2dOptions};
+pub use dropout::{AlphaDropout, Dropout, Dropout2d};
+pub use flatten::Flatten;
 pub use linear::Linear;
+pub use norm::{BatchNorm1d, BatchNorm2d, GroupNorm, LayerNorm, RMSNorm};
+pub use pool::{AdaptiveAvgPool2d, AvgPool2d, MaxPool2d};
 pub use safe_module::SafeModule;
-pub use sequential::Sequential;
+pub use sequential::{LayerShape, ModuleDebug, Sequential};
+pub use state_dict::StateDict;
+pub use summary::Summary;
 
-pub struct Parameter<'a> {
-    pub parm: &'a mut Array2<f64>,
-    pub grad: &'a mut Array2<f64>,
+/// Learnable array of a module and its gradient. The name is unique within the module that
+/// returned it, e.g. `weight`, or `0.weight` for the first layer of a [`Sequential`], so it
+/// identifies the parameter between calls to [`Module::parameters`].
+pub struct Parameter<'a, F = f64> {
+    pub name: String,
+    pub parm: &'a mut ArrayD<F>,
+    pub grad: &'a mut ArrayD<F>,
+    /// Flag stored by the module, if the parameter can be frozen
+    requires_grad: Option<&'a mut bool>,
 }
 
-pub struct Parameters<'a> {
-    parms: Vec<Parameter<'a>>,
+impl<F> Parameter<'_, F> {
+    /// Whether the parameter is trained, see [`Module::freeze`].
+    #[inline]
+    pub fn requires_grad(&self) -> bool {
+        self.requires_grad.as_deref().copied().unwrap_or(true)
+    }
+
+    /// Freezes the parameter when `false`: its gradient is no longer computed and optimizers
+    /// leave it unchanged.
+    ///
+    /// # Panics
+    ///
+    /// When freezing a parameter added without a flag by [`Parameters::add`].
+    #[inline]
+    pub fn set_requires_grad(&mut self, requires_grad: bool) {
+        match &mut self.requires_grad {
+            Some(flag) => **flag = requires_grad,
+            None => assert!(requires_grad, "Parameter {} cannot be frozen", self.name),
+        }
+    }
 }
 
-impl<'a> Parameters<'a> {
-    fn new(size: usize) -> Self {
+pub struct Parameters<'a, F = f64> {
+    parms: Vec<Parameter<'a, F>>,
+}
+
+impl<'a, F> Parameters<'a, F> {
+    pub(crate) fn new(size: usize) -> Self {
         Self {
             parms: Vec::with_capacity(size),
         }
     }
 
-    pub fn add(mut self, parm: &'a mut Array2<f64>, grad: &'a mut Array2<f64>) -> Self {
-        self.parms.push(Parameter { parm, grad });
+    /// Adds a parameter that is always trained.
+    pub fn add(mut self, name: &str, parm: &'a mut ArrayD<F>, grad: &'a mut ArrayD<F>) -> Self {
+        self.parms.push(Parameter {
+            name: name.to_string(),
+            parm,
+            grad,
+            requires_grad: None,
+        });
+        self
+    }
+
+    /// Adds a parameter that can be frozen by setting `requires_grad`, which the module should
+    /// check before computing its gradient.
+    pub fn add_with_requires_grad(
+        mut self,
+        name: &str,
+        parm: &'a mut ArrayD<F>,
+        grad: &'a mut ArrayD<F>,
+        requires_grad: &'a mut bool,
+    ) -> Self {
+        self.parms.push(Parameter {
+            name: name.to_string(),
+            parm,
+            grad,
+            requires_grad: Some(requires_grad),
+        });
+        self
+    }
+
+    /// Nests the parameters of a submodule under `prefix`, so `weight` becomes `prefix.weight`.
+    #[must_use]
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        for parm in &mut self.parms {
+            parm.name = format!("{prefix}.{}", parm.name);
+        }
         self
     }
 
-    pub fn iter(self) -> impl Iterator<Item = Parameter<'a>> {
+    pub fn iter(self) -> impl Iterator<Item = Parameter<'a, F>> {
         self.parms.into_iter()
     }
+
+    /// Parameters that are not frozen.
+    pub fn trainable(self) -> impl Iterator<Item = Parameter<'a, F>> {
+        self.iter().filter(Parameter::requires_grad)
+    }
+}
+
+/// Array of a module saved in its state dict but not learned, like the running statistics of a
+/// batch normalization. Named like the parameters.
+pub struct Buffer<'a, F = f64> {
+    pub name: String,
+    pub buffer: &'a mut ArrayD<F>,
+}
+
+pub struct Buffers<'a, F = f64> {
+    buffers: Vec<Buffer<'a, F>>,
+}
+
+impl<'a, F> Buffers<'a, F> {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            buffers: Vec::with_capacity(size),
+        }
+    }
+
+    pub fn add(mut self, name: &str, buffer: &'a mut ArrayD<F>) -> Self {
+        self.buffers.push(Buffer {
+            name: name.to_string(),
+            buffer,
+        });
+        self
+    }
+
+    /// Nests the buffers of a submodule under `prefix`, like [`Parameters::prefix`].
+    #[must_use]
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        for buffer in &mut self.buffers {
+            buffer.name = format!("{prefix}.{}", buffer.name);
+        }
+        self
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Buffer<'a, F>> {
+        self.buffers.into_iter()
+    }
+}
+
+/// Takes the values saved by the forward pass of `module` for its backward pass.
+#[inline]
+pub(crate) fn saved<T>(value: &mut Option<T>, module: &str) -> Result<T> {
+    value
+        .take()
+        .ok_or_else(|| Error::State(format!("{module}::backward called before forward")))
+}
+
+/// Error of a module that cannot take an input of shape `input`.
+#[inline]
+pub(crate) fn incompatible(layer: &str, input: &[usize], reason: impl Into<String>) -> Error {
+    Error::IncompatibleShape {
+        layer: layer.to_string(),
+        input: input.to_vec(),
+        reason: reason.into(),
+    }
+}
+
+/// Sum over every axis but the channel one: (batch_size, channels, *) -> (channels)
+pub(crate) fn channel_sum<F: Float>(x: &ArrayD<F>) -> ArrayD<F> {
+    let mut sum = x.sum_axis(Axis(0));
+    while sum.ndim() > 1 {
+        sum = sum.sum_axis(Axis(1));
+    }
+    sum
+}
+
+/// (channels) -> (1, channels, 1, ...), to broadcast over an input with `ndim` dimensions
+pub(crate) fn per_channel<F: Float>(x: &ArrayD<F>, ndim: usize) -> ArrayD<F> {
+    let mut shape = vec![1; ndim];
+    shape[1] = x.len();
+    x.clone().into_shape(shape).unwrap()
+}
+
+/// Gradient of `f` at `x` by central differences, to test the backward passes.
+#[cfg(test)]
+pub(crate) fn numeric_gradient(
+    mut f: impl FnMut(ArrayD<f64>) -> f64,
+    x: &ArrayD<f64>,
+) -> ArrayD<f64> {
+    let delta = 1e-6;
+    let mut grad = ArrayD::zeros(x.raw_dim());
+    for (index, g) in grad.indexed_iter_mut() {
+        let (mut plus, mut minus) = (x.clone(), x.clone());
+        plus[&index] += delta;
+        minus[&index] -= delta;
+        *g = (f(plus) - f(minus)) / (2.0 * delta);
+    }
+    grad
+}
+
+/// Checks the gradients of the input and of the parameters computed by `backward` against
+/// central differences of `sum(forward(data) * weights)`.
+#[cfg(test)]
+pub(crate) fn check_gradients(module: &mut dyn Module<f64>, data: &ArrayD<f64>) {
+    let weights = data.mapv(|x| (x * 0.37 + 0.1).sin());
+    let n = data.shape()[0] as f64;
+    module.zero_grad();
+    module.forward(data.clone());
+    let result = module.backward(weights.clone()).unwrap();
+    let expected = numeric_gradient(|x| (module.forward(x) * &weights).sum(), data);
+    crate::assert_array_eq!(result, expected, 1e-5);
+
+    let parameters: Vec<_> = module
+        .parameters()
+        .iter()
+        .map(|p| (p.parm.clone(), p.grad.clone()))
+        .collect();
+    for (i, (parm, grad)) in parameters.into_iter().enumerate() {
+        let set = |module: &mut dyn Module<f64>, value: &ArrayD<f64>| {
+            module
+                .parameters()
+                .iter()
+                .nth(i)
+                .unwrap()
+                .parm
+                .assign(value);
+        };
+        let expected = numeric_gradient(
+            |value| {
+                set(module, &value);
+                (module.forward(data.clone()) * &weights).sum()
+            },
+            &parm,
+        ) / n;
+        set(module, &parm);
+        crate::assert_array_eq!(grad, expected, 1e-5);
+    }
 }
 
-pub trait Module {
-    /// (batch_size, input_size) -> (batch_size, output_size)
-    fn forward(&mut self, input: Array2<f64>) -> Array2<f64>;
+pub trait Module<F: Float = f64> {
+    /// (batch_size, *input_shape) -> (batch_size, *output_shape)
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F>;
+
+    /// (batch_size, *output_shape) -> (batch_size, *input_shape)
+    ///
+    /// The gradients of the parameters are added to the current ones until
+    /// [`zero_grad`](Module::zero_grad), so several batches can be accumulated before a step.
+    /// Fails with [`Error::State`] without a previous forward pass.
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>>;
+
+    fn parameters(&mut self) -> Parameters<'_, F>;
+
+    /// Arrays that are not learned but saved with the parameters in the
+    /// [state dict](Module::state_dict), e.g. running statistics.
+    #[inline]
+    fn buffers(&mut self) -> Buffers<'_, F> {
+        Buffers::new(0)
+    }
+
+    /// Shape of the output for an input of shape `(batch_size, *input_shape)`, computed without
+    /// running the module. Fails with [`Error::IncompatibleShape`] if the module cannot take such
+    /// an input, or does not support shape inference.
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        Err(incompatible(
+            std::any::type_name::<Self>(),
+            input_shape,
+            "shape inference is not supported",
+        ))
+    }
+
+    /// Resets the gradients of the parameters to zero, usually before each backward pass.
+    #[inline]
+    fn zero_grad(&mut self) {
+        self.parameters()
+            .iter()
+            .for_each(|parameter| parameter.grad.fill(F::zero()));
+    }
+
+    /// Parameters of the module and of its submodules, by [name](Parameter::name).
+    #[inline]
+    fn named_parameters(&mut self) -> Vec<Parameter<'_, F>> {
+        self.parameters().iter().collect()
+    }
+
+    /// Number of learnable values.
+    #[inline]
+    fn num_parameters(&mut self) -> usize {
+        self.parameters().iter().map(|p| p.parm.len()).sum()
+    }
 
-    /// (batch_size, output_size) -> (batch_size, input_size)
-    fn backward(&mut self, gradient: Array2<f64>) -> Array2<f64>;
+    /// Freezes (`false`) or unfreezes every parameter of the module and of its submodules, see
+    /// [`Parameter::set_requires_grad`].
+    #[inline]
+    fn set_requires_grad(&mut self, requires_grad: bool) {
+        self.parameters()
+            .iter()
+            .for_each(|mut parameter| parameter.set_requires_grad(requires_grad));
+    }
 
-    fn parameters(&mut self) -> Parameters<'_>;
+    /// Stops training the module, e.g. the backbone of a pretrained model.
+    #[inline]
+    fn freeze(&mut self) {
+        self.set_requires_grad(false)
+    }
+
+    #[inline]
+    fn unfreeze(&mut self) {
+        self.set_requires_grad(true)
+    }
+
+    /// Direct submodules by name, e.g. the layers of a [`Sequential`] by index.
+    #[inline]
+    fn children(&mut self) -> Vec<(String, &mut dyn Module<F>)> {
+        Vec::new()
+    }
+
+    /// Calls `f` with every submodule, before its own submodules, named by its path like the
+    /// parameters, e.g. `1.0` for the first layer of the second one. Submodules are visited
+    /// instead of returned, as a module cannot be borrowed along with its submodules.
+    fn named_modules(&mut self, f: &mut dyn FnMut(&str, &mut dyn Module<F>)) {
+        for (name, child) in self.children() {
+            f(&name, &mut *child);
+            child.named_modules(&mut |path, module| f(&format!("{name}.{path}"), module));
+        }
+    }
+
+    /// Copy of the parameters and buffers of the module by [name](Parameter::name).
+    fn state_dict(&mut self) -> StateDict<F> {
+        let mut state_dict: StateDict<F> = self
+            .parameters()
+            .iter()
+            .map(|Parameter { name, parm, .. }| (name, parm.clone()))
+            .collect();
+        for Buffer { name, buffer } in self.buffers().iter() {
+            state_dict.insert(name, buffer.clone());
+        }
+        state_dict
+    }
+
+    /// Copies the arrays of the state dict to the parameters and buffers with the same name.
+    /// Fails without modifying the module if the names are not exactly those of the parameters
+    /// and buffers, or if any shape differs.
+    fn load_state_dict(&mut self, state_dict: &StateDict<F>) -> Result<()> {
+        let mut shapes: Vec<_> = self
+            .parameters()
+            .iter()
+            .map(|p| (p.name, p.parm.shape().to_vec()))
+            .collect();
+        shapes.extend(
+            self.buffers()
+                .iter()
+                .map(|b| (b.name, b.buffer.shape().to_vec())),
+        );
+
+        let missing: Vec<_> = shapes
+            .iter()
+            .filter(|(name, _)| !state_dict.contains_key(name))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let unexpected: Vec<_> = state_dict
+            .keys()
+            .filter(|&key| shapes.iter().all(|(name, _)| name != key))
+            .map(str::to_string)
+            .collect();
+        if !missing.is_empty() || !unexpected.is_empty() {
+            return Err(Error::StateDictKeys {
+                missing,
+                unexpected,
+            });
+        }
+
+        for (name, shape) in shapes {
+            let array = state_dict.get(&name).unwrap();
+            if shape != array.shape() {
+                return Err(Error::ShapeMismatch {
+                    name,
+                    expected: shape,
+                    found: array.shape().to_vec(),
+                });
+            }
+        }
+
+        for Parameter { name, parm, .. } in self.parameters().iter() {
+            parm.assign(state_dict.get(&name).unwrap());
+        }
+        for Buffer { name, buffer } in self.buffers().iter() {
+            buffer.assign(state_dict.get(&name).unwrap());
+        }
+        Ok(())
+    }
 
     #[inline]
     fn train(&mut self) {}
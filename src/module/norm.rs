@@ -0,0 +1,861 @@
+use crate::module::{channel_sum, incompatible, per_channel, saved, Buffers, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+
+/// Values saved by the forward pass of a batch normalization.
+#[derive(Debug)]
+struct BatchNormSaved<F> {
+    normalized: ArrayD<F>,
+    /// (1, channels, 1, ...)
+    inv_std: ArrayD<F>,
+    /// Whether the statistics of the batch were used, rather than the running ones
+    batch_stats: bool,
+}
+
+/// Normalization over every axis but the channel one, shared by [`BatchNorm1d`] and
+/// [`BatchNorm2d`].
+#[derive(Debug)]
+struct BatchNorm<F> {
+    /// (channels)
+    weight: ArrayD<F>,
+    /// (channels)
+    bias: ArrayD<F>,
+    running_mean: ArrayD<F>,
+    running_var: ArrayD<F>,
+    momentum: f64,
+    eps: f64,
+    training: bool,
+
+    prev: Option<BatchNormSaved<F>>,
+    grad_weight: ArrayD<F>,
+    grad_bias: ArrayD<F>,
+    weight_requires_grad: bool,
+    bias_requires_grad: bool,
+}
+
+impl<F: Float> BatchNorm<F> {
+    fn new(num_features: usize) -> Self {
+        Self {
+            weight: ArrayD::ones(vec![num_features]),
+            bias: ArrayD::zeros(vec![num_features]),
+            running_mean: ArrayD::zeros(vec![num_features]),
+            running_var: ArrayD::ones(vec![num_features]),
+            momentum: 0.1,
+            eps: 1e-5,
+            training: true,
+            prev: None,
+            grad_weight: ArrayD::zeros(vec![num_features]),
+            grad_bias: ArrayD::zeros(vec![num_features]),
+            weight_requires_grad: true,
+            bias_requires_grad: true,
+        }
+    }
+
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let ndim = input.ndim();
+        let (mean, var) = match self.training {
+            true => {
+                let m = input.len() / self.weight.len();
+                assert!(
+                    m > 1,
+                    "Batch normalization needs more than 1 value per channel"
+                );
+                let mean = channel_sum(&input) / F::from_usize(m);
+                let centered = &input - &per_channel(&mean, ndim);
+                let var = channel_sum(&centered.mapv(|x| x * x)) / F::from_usize(m);
+
+                let momentum = F::from_f64(self.momentum);
+                let unbiased = &var * F::from_usize(m) / F::from_usize(m - 1);
+                self.running_mean = &self.running_mean * (F::one() - momentum) + &mean * momentum;
+                self.running_var = &self.running_var * (F::one() - momentum) + unbiased * momentum;
+                (mean, var)
+            }
+            false => (self.running_mean.clone(), self.running_var.clone()),
+        };
+
+        let eps = F::from_f64(self.eps);
+        let inv_std = per_channel(&var.mapv(|v| (v + eps).sqrt().recip()), ndim);
+        let normalized = (input - per_channel(&mean, ndim)) * &inv_std;
+        let output = &normalized * &per_channel(&self.weight, ndim) + per_channel(&self.bias, ndim);
+
+        self.prev = Some(BatchNormSaved {
+            normalized,
+            inv_std,
+            batch_stats: self.training,
+        });
+        output
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>, layer: &str) -> Result<ArrayD<F>> {
+        let BatchNormSaved {
+            normalized,
+            inv_std,
+            batch_stats,
+        } = saved(&mut self.prev, layer)?;
+        let ndim = gradient.ndim();
+        let n = F::from_usize(gradient.shape()[0]);
+
+        if self.weight_requires_grad {
+            self.grad_weight += &(channel_sum(&(&gradient * &normalized)) / n);
+        }
+        if self.bias_requires_grad {
+            self.grad_bias += &(channel_sum(&gradient) / n);
+        }
+
+        let grad_normalized = gradient * per_channel(&self.weight, ndim);
+        if !batch_stats {
+            return Ok(grad_normalized * inv_std);
+        }
+
+        // The mean and variance depend on the input too
+        let m = F::from_usize(normalized.len() / self.weight.len());
+        let mean = per_channel(&(channel_sum(&grad_normalized) / m), ndim);
+        let product = channel_sum(&(&grad_normalized * &normalized)) / m;
+        Ok((grad_normalized - mean - normalized * per_channel(&product, ndim)) * inv_std)
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(2)
+            .add_with_requires_grad(
+                "weight",
+                &mut self.weight,
+                &mut self.grad_weight,
+                &mut self.weight_requires_grad,
+            )
+            .add_with_requires_grad(
+                "bias",
+                &mut self.bias,
+                &mut self.grad_bias,
+                &mut self.bias_requires_grad,
+            )
+    }
+
+    fn buffers(&mut self) -> Buffers<'_, F> {
+        Buffers::new(2)
+            .add("running_mean", &mut self.running_mean)
+            .add("running_var", &mut self.running_var)
+    }
+
+    /// Checks that the input has one of the numbers of dimensions `ndims` and the right number of
+    /// channels.
+    fn output_shape(
+        &self,
+        layer: &str,
+        input_shape: &[usize],
+        ndims: &[usize],
+        expected: &str,
+    ) -> Result<Vec<usize>> {
+        if !ndims.contains(&input_shape.len()) {
+            return Err(incompatible(layer, input_shape, expected));
+        }
+        let channels = self.weight.len();
+        match input_shape[1] == channels {
+            true => Ok(input_shape.to_vec()),
+            false => {
+                let reason = format!("expected {channels} channels, found {}", input_shape[1]);
+                Err(incompatible(layer, input_shape, reason))
+            }
+        }
+    }
+}
+
+macro_rules! batch_norm {
+    ($(#[$attr:meta])* $name:ident, $ndims:expr, $expected:literal) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        pub struct $name<F = f64>(BatchNorm<F>);
+
+        impl<F: Float> $name<F> {
+            #[inline]
+            #[must_use]
+            pub fn new(num_features: usize) -> Self {
+                Self(BatchNorm::new(num_features))
+            }
+
+            /// Weight of the statistics of a batch in the running ones, 0.1 by default.
+            #[inline]
+            #[must_use]
+            pub fn momentum(mut self, momentum: f64) -> Self {
+                self.0.momentum = momentum;
+                self
+            }
+
+            /// Added to the variance for numerical stability, 1e-5 by default.
+            #[inline]
+            #[must_use]
+            pub fn eps(mut self, eps: f64) -> Self {
+                self.0.eps = eps;
+                self
+            }
+        }
+
+        impl<F: Float> Module<F> for $name<F> {
+            fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+                if let Err(error) = Module::output_shape(self, input.shape()) {
+                    panic!("{error}");
+                }
+                self.0.forward(input)
+            }
+
+            #[inline]
+            fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+                self.0.backward(gradient, stringify!($name))
+            }
+
+            #[inline]
+            fn parameters(&mut self) -> Parameters<'_, F> {
+                self.0.parameters()
+            }
+
+            #[inline]
+            fn buffers(&mut self) -> Buffers<'_, F> {
+                self.0.buffers()
+            }
+
+            #[inline]
+            fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+                self.0
+                    .output_shape(stringify!($name), input_shape, $ndims, $expected)
+            }
+
+            #[inline]
+            fn train(&mut self) {
+                self.0.training = true;
+            }
+
+            #[inline]
+            fn eval(&mut self) {
+                self.0.training = false;
+            }
+        }
+    };
+}
+
+batch_norm!(
+    /// Normalizes every channel of an input of shape `(batch_size, channels)` or
+    /// `(batch_size, channels, length)` with the mean and variance of the batch in training mode,
+    /// then applies a learned scale (`weight`) and shift (`bias`). The running mean and variance,
+    /// updated in training mode and used instead in evaluation mode, are saved as buffers.
+    BatchNorm1d,
+    &[2, 3],
+    "expected a shape (batch_size, channels) or (batch_size, channels, length)"
+);
+
+batch_norm!(
+    /// [`BatchNorm1d`] for an input of shape `(batch_size, channels, height, width)`, normalizing
+    /// every channel over the batch and both spatial dimensions.
+    BatchNorm2d,
+    &[4],
+    "expected a shape (batch_size, channels, height, width)"
+);
+
+/// Rows normalized to zero mean and unit variance, and the inverse of the standard deviation of
+/// every row: (rows, size) -> ((rows, size), (rows, 1))
+fn standardize<F: Float>(x: Array2<F>, eps: F) -> (Array2<F>, Array2<F>) {
+    let size = F::from_usize(x.ncols());
+    let mean = (x.sum_axis(Axis(1)) / size).insert_axis(Axis(1));
+    let centered = x - &mean;
+    let var = (centered.mapv(|x| x * x).sum_axis(Axis(1)) / size).insert_axis(Axis(1));
+    let inv_std = var.mapv(|v| (v + eps).sqrt().recip());
+    (centered * &inv_std, inv_std)
+}
+
+/// Gradient of the rows given to [`standardize`] from the gradient of the normalized ones.
+fn standardize_backward<F: Float>(
+    gradient: Array2<F>,
+    normalized: ArrayView2<'_, F>,
+    inv_std: &Array2<F>,
+) -> Array2<F> {
+    let size = F::from_usize(gradient.ncols());
+    let mean = (gradient.sum_axis(Axis(1)) / size).insert_axis(Axis(1));
+    let product = ((&gradient * &normalized).sum_axis(Axis(1)) / size).insert_axis(Axis(1));
+    (gradient - mean - &normalized * &product) * inv_std
+}
+
+/// (*, normalized_shape) -> (prod(*), prod(normalized_shape))
+fn into_rows<F: Float>(x: ArrayD<F>, normalized_shape: &[usize]) -> Array2<F> {
+    let size = normalized_shape.iter().product::<usize>();
+    x.as_standard_layout()
+        .into_owned()
+        .into_shape((x.len() / size.max(1), size))
+        .unwrap()
+}
+
+/// Checks that the input has a batch dimension and ends with `normalized_shape`.
+fn trailing_shape(
+    layer: &str,
+    input_shape: &[usize],
+    normalized_shape: &[usize],
+) -> Result<Vec<usize>> {
+    match input_shape.len() > normalized_shape.len() && input_shape.ends_with(normalized_shape) {
+        true => Ok(input_shape.to_vec()),
+        false => {
+            let reason = format!("expected a shape (batch_size, *, {normalized_shape:?})");
+            Err(incompatible(layer, input_shape, reason))
+        }
+    }
+}
+
+/// Learned elementwise scale (`weight`) and optional shift (`bias`) of a normalization.
+#[derive(Debug)]
+struct Affine<F> {
+    weight: ArrayD<F>,
+    bias: Option<ArrayD<F>>,
+
+    grad_weight: ArrayD<F>,
+    grad_bias: Option<ArrayD<F>>,
+    weight_requires_grad: bool,
+    bias_requires_grad: bool,
+}
+
+impl<F: Float> Affine<F> {
+    fn new(shape: &[usize], bias: bool) -> Self {
+        Self {
+            weight: ArrayD::ones(shape),
+            bias: bias.then(|| ArrayD::zeros(shape)),
+            grad_weight: ArrayD::zeros(shape),
+            grad_bias: bias.then(|| ArrayD::zeros(shape)),
+            weight_requires_grad: true,
+            bias_requires_grad: true,
+        }
+    }
+
+    /// Adds the gradient of the weight, only computed if it is not frozen.
+    fn accumulate_weight(&mut self, grad_weight: impl FnOnce() -> ArrayD<F>) {
+        if self.weight_requires_grad {
+            self.grad_weight += &grad_weight();
+        }
+    }
+
+    /// Adds the gradient of the bias, only computed if there is one and it is not frozen.
+    fn accumulate_bias(&mut self, grad_bias: impl FnOnce() -> ArrayD<F>) {
+        if let Some(grad) = self.grad_bias.as_mut().filter(|_| self.bias_requires_grad) {
+            *grad += &grad_bias();
+        }
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        let params = Parameters::new(2).add_with_requires_grad(
+            "weight",
+            &mut self.weight,
+            &mut self.grad_weight,
+            &mut self.weight_requires_grad,
+        );
+        match (self.bias.as_mut(), self.grad_bias.as_mut()) {
+            (Some(bias), Some(grad_bias)) => {
+                params.add_with_requires_grad("bias", bias, grad_bias, &mut self.bias_requires_grad)
+            }
+            _ => params,
+        }
+    }
+}
+
+/// Normalizes every sample over its trailing dimensions `normalized_shape` to zero mean and unit
+/// variance, then applies a learned elementwise scale and shift of shape `normalized_shape`:
+/// `(batch_size, *, *normalized_shape) -> (batch_size, *, *normalized_shape)`.
+#[derive(Debug)]
+pub struct LayerNorm<F = f64> {
+    normalized_shape: Vec<usize>,
+    eps: f64,
+    affine: Option<Affine<F>>,
+
+    /// Normalized rows of the last input and the inverse of their standard deviation
+    prev: Option<(Array2<F>, Array2<F>)>,
+}
+
+impl<F: Float> LayerNorm<F> {
+    #[inline]
+    #[must_use]
+    pub fn new(normalized_shape: &[usize]) -> Self {
+        Self {
+            normalized_shape: normalized_shape.to_vec(),
+            eps: 1e-5,
+            affine: Some(Affine::new(normalized_shape, true)),
+            prev: None,
+        }
+    }
+
+    /// Whether the output is scaled and shifted by learned parameters, true by default.
+    #[inline]
+    #[must_use]
+    pub fn elementwise_affine(mut self, elementwise_affine: bool) -> Self {
+        self.affine = elementwise_affine.then(|| Affine::new(&self.normalized_shape, true));
+        self
+    }
+
+    /// Added to the variance for numerical stability, 1e-5 by default.
+    #[inline]
+    #[must_use]
+    pub fn eps(mut self, eps: f64) -> Self {
+        self.eps = eps;
+        self
+    }
+}
+
+impl<F: Float> Module<F> for LayerNorm<F> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        if let Err(error) = self.output_shape(input.shape()) {
+            panic!("{error}");
+        }
+        let shape = input.shape().to_vec();
+        let rows = into_rows(input, &self.normalized_shape);
+        let (normalized, inv_std) = standardize(rows, F::from_f64(self.eps));
+
+        let mut output = normalized.clone().into_shape(shape).unwrap();
+        if let Some(affine) = &self.affine {
+            output = output * &affine.weight + affine.bias.as_ref().unwrap();
+        }
+        self.prev = Some((normalized, inv_std));
+        output
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let (normalized, inv_std) = saved(&mut self.prev, "LayerNorm")?;
+        let shape = gradient.shape().to_vec();
+        let n = F::from_usize(shape[0]);
+
+        let mut gradient = into_rows(gradient, &self.normalized_shape);
+        if let Some(affine) = &mut self.affine {
+            let weight_shape = affine.weight.raw_dim();
+            affine.accumulate_weight(|| {
+                let sum = (&gradient * &normalized).sum_axis(Axis(0)) / n;
+                sum.into_shape(weight_shape.clone()).unwrap()
+            });
+            affine.accumulate_bias(|| {
+                let sum = gradient.sum_axis(Axis(0)) / n;
+                sum.into_shape(weight_shape).unwrap()
+            });
+            gradient *= &affine.weight.view().into_shape(normalized.ncols()).unwrap();
+        }
+
+        let grad = standardize_backward(gradient, normalized.view(), &inv_std);
+        Ok(grad.into_shape(shape).unwrap())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        match &mut self.affine {
+            Some(affine) => affine.parameters(),
+            None => Parameters::new(0),
+        }
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        trailing_shape("LayerNorm", input_shape, &self.normalized_shape)
+    }
+}
+
+/// Splits the channels of an input of shape `(batch_size, channels, *)` into `num_groups` groups
+/// and normalizes every group of every sample to zero mean and unit variance, then applies a
+/// learned scale and shift per channel.
+#[derive(Debug)]
+pub struct GroupNorm<F = f64> {
+    num_groups: usize,
+    num_channels: usize,
+    eps: f64,
+    affine: Option<Affine<F>>,
+
+    /// Normalized input and the inverse of the standard deviation of every group, (batch_size *
+    /// num_groups, 1)
+    prev: Option<(ArrayD<F>, Array2<F>)>,
+}
+
+impl<F: Float> GroupNorm<F> {
+    /// # Panics
+    ///
+    /// If `num_channels` is not a multiple of `num_groups`.
+    #[inline]
+    #[must_use]
+    pub fn new(num_groups: usize, num_channels: usize) -> Self {
+        assert!(
+            num_groups > 0 && num_channels.is_multiple_of(num_groups),
+            "Number of channels {num_channels} must be a multiple of the number of groups {num_groups}"
+        );
+        Self {
+            num_groups,
+            num_channels,
+            eps: 1e-5,
+            affine: Some(Affine::new(&[num_channels], true)),
+            prev: None,
+        }
+    }
+
+    /// Whether the output is scaled and shifted by learned parameters, true by default.
+    #[inline]
+    #[must_use]
+    pub fn affine(mut self, affine: bool) -> Self {
+        self.affine = affine.then(|| Affine::new(&[self.num_channels], true));
+        self
+    }
+
+    /// Added to the variance for numerical stability, 1e-5 by default.
+    #[inline]
+    #[must_use]
+    pub fn eps(mut self, eps: f64) -> Self {
+        self.eps = eps;
+        self
+    }
+}
+
+impl<F: Float> Module<F> for GroupNorm<F> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        if let Err(error) = self.output_shape(input.shape()) {
+            panic!("{error}");
+        }
+        let shape = input.shape().to_vec();
+        let groups = shape[0] * self.num_groups;
+        let rows = into_rows(input, &[shape.iter().product::<usize>() / groups]);
+        let (normalized, inv_std) = standardize(rows, F::from_f64(self.eps));
+        let normalized = normalized.into_shape(shape).unwrap();
+
+        let output = match &self.affine {
+            Some(affine) => {
+                let ndim = normalized.ndim();
+                let bias = affine.bias.as_ref().unwrap();
+                &normalized * &per_channel(&affine.weight, ndim) + per_channel(bias, ndim)
+            }
+            None => normalized.clone(),
+        };
+        self.prev = Some((normalized, inv_std));
+        output
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let (normalized, inv_std) = saved(&mut self.prev, "GroupNorm")?;
+        let shape = gradient.shape().to_vec();
+        let n = F::from_usize(shape[0]);
+
+        let mut gradient = gradient;
+        if let Some(affine) = &mut self.affine {
+            affine.accumulate_weight(|| channel_sum(&(&gradient * &normalized)) / n);
+            affine.accumulate_bias(|| channel_sum(&gradient) / n);
+            gradient = gradient * per_channel(&affine.weight, shape.len());
+        }
+
+        let rows = (inv_std.len(), normalized.len() / inv_std.len());
+        let gradient = into_rows(gradient, &[rows.1]);
+        let normalized = normalized.view().into_shape(rows).unwrap();
+        let grad = standardize_backward(gradient, normalized, &inv_std);
+        Ok(grad.into_shape(shape).unwrap())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        match &mut self.affine {
+            Some(affine) => affine.parameters(),
+            None => Parameters::new(0),
+        }
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        match input_shape {
+            [_, channels, ..] if *channels == self.num_channels => Ok(input_shape.to_vec()),
+            [_, channels, ..] => {
+                let reason = format!("expected {} channels, found {channels}", self.num_channels);
+                Err(incompatible("GroupNorm", input_shape, reason))
+            }
+            _ => Err(incompatible(
+                "GroupNorm",
+                input_shape,
+                "expected a shape (batch_size, channels, *)",
+            )),
+        }
+    }
+}
+
+/// Divides every sample by the root mean square over its trailing dimensions `normalized_shape`,
+/// without centering it, then applies a learned elementwise scale of shape `normalized_shape`.
+#[derive(Debug)]
+pub struct RMSNorm<F = f64> {
+    normalized_shape: Vec<usize>,
+    /// `None` for the machine epsilon of `F`
+    eps: Option<f64>,
+    affine: Option<Affine<F>>,
+
+    /// Normalized rows of the last input and the inverse of their root mean square
+    prev: Option<(Array2<F>, Array2<F>)>,
+}
+
+impl<F: Float> RMSNorm<F> {
+    #[inline]
+    #[must_use]
+    pub fn new(normalized_shape: &[usize]) -> Self {
+        Self {
+            normalized_shape: normalized_shape.to_vec(),
+            eps: None,
+            affine: Some(Affine::new(normalized_shape, false)),
+            prev: None,
+        }
+    }
+
+    /// Whether the output is scaled by a learned parameter, true by default.
+    #[inline]
+    #[must_use]
+    pub fn elementwise_affine(mut self, elementwise_affine: bool) -> Self {
+        self.affine = elementwise_affine.then(|| Affine::new(&self.normalized_shape, false));
+        self
+    }
+
+    /// Added to the mean square for numerical stability, the machine epsilon of `F` by default.
+    #[inline]
+    #[must_use]
+    pub fn eps(mut self, eps: f64) -> Self {
+        self.eps = Some(eps);
+        self
+    }
+}
+
+impl<F: Float> Module<F> for RMSNorm<F> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        if let Err(error) = self.output_shape(input.shape()) {
+            panic!("{error}");
+        }
+        let shape = input.shape().to_vec();
+        let rows = into_rows(input, &self.normalized_shape);
+        let eps = self.eps.map_or(F::epsilon(), F::from_f64);
+        let size = F::from_usize(rows.ncols());
+        let mean_square = rows.mapv(|x| x * x).sum_axis(Axis(1)) / size;
+        let inv_rms = mean_square
+            .mapv(|v| (v + eps).sqrt().recip())
+            .insert_axis(Axis(1));
+        let normalized = rows * &inv_rms;
+
+        let mut output = normalized.clone().into_shape(shape).unwrap();
+        if let Some(affine) = &self.affine {
+            output *= &affine.weight;
+        }
+        self.prev = Some((normalized, inv_rms));
+        output
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let (normalized, inv_rms) = saved(&mut self.prev, "RMSNorm")?;
+        let shape = gradient.shape().to_vec();
+        let n = F::from_usize(shape[0]);
+
+        let mut gradient = into_rows(gradient, &self.normalized_shape);
+        if let Some(affine) = &mut self.affine {
+            let weight_shape = affine.weight.raw_dim();
+            affine.accumulate_weight(|| {
+                let sum = (&gradient * &normalized).sum_axis(Axis(0)) / n;
+                sum.into_shape(weight_shape).unwrap()
+            });
+            gradient *= &affine.weight.view().into_shape(normalized.ncols()).unwrap();
+        }
+
+        // Like `standardize_backward`, without the mean
+        let size = F::from_usize(gradient.ncols());
+        let product = ((&gradient * &normalized).sum_axis(Axis(1)) / size).insert_axis(Axis(1));
+        let grad = (gradient - normalized * &product) * &inv_rms;
+        Ok(grad.into_shape(shape).unwrap())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        match &mut self.affine {
+            Some(affine) => affine.parameters(),
+            None => Parameters::new(0),
+        }
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        trailing_shape("RMSNorm", input_shape, &self.normalized_shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+    use crate::module::{check_gradients, Linear, Sequential};
+    use crate::sequential;
+
+    #[test]
+    fn forward() {
+        let mut module = BatchNorm1d::new(2);
+        let data = array![[1.0, 10.0], [2.0, 10.0], [6.0, 40.0]].into_dyn();
+        let result = module.forward(data);
+        let mean = result.mean_axis(Axis(0)).unwrap();
+        let var = result.var_axis(Axis(0), 0.0);
+        assert!(mean.iter().all(|x: &f64| x.abs() < 1e-6), "{mean}");
+        assert!(var.iter().all(|x: &f64| (x - 1.0).abs() < 1e-4), "{var}");
+
+        // The unbiased variances are 7 and 300
+        let running_mean = module.0.running_mean.clone();
+        let running_var = module.0.running_var.clone();
+        let expected_mean = array![0.3, 2.0].into_dyn();
+        let expected_var = array![0.9 + 0.7, 0.9 + 30.0].into_dyn();
+        assert_array_eq!(running_mean, expected_mean);
+        assert_array_eq!(running_var, expected_var);
+
+        module.eval();
+        let result = module.forward(array![[0.3, 2.0 + 30.9f64.sqrt()]].into_dyn());
+        let expected = array![[0.0, 1.0]].into_dyn();
+        assert_array_eq!(result, expected, 1e-4);
+    }
+
+    /// Input of shape (3, 2, 2) with different statistics in every sample and channel
+    fn data() -> ArrayD<f64> {
+        Array::from_shape_fn((3, 2, 2), |(i, j, k)| {
+            ((i * 7 + j * 3 + k * 5) % 11) as f64 - j as f64
+        })
+        .into_dyn()
+    }
+
+    #[test]
+    fn backward() {
+        for training in [true, false] {
+            let mut module = BatchNorm1d::new(2);
+            module.0.weight = array![0.5, 2.0].into_dyn();
+            module.0.bias = array![1.0, -1.0].into_dyn();
+            module.0.running_mean = array![1.0, 2.0].into_dyn();
+            module.0.running_var = array![4.0, 0.5].into_dyn();
+            if !training {
+                module.eval();
+            }
+            // The momentum is zero, so that the running statistics do not change
+            module.0.momentum = 0.0;
+            check_gradients(&mut module, &data());
+        }
+    }
+
+    #[test]
+    fn backward_parameters() {
+        let mut module = BatchNorm2d::new(1);
+        let data = Array::from_shape_fn((2, 1, 2, 2), |(i, _, j, k)| (i + 2 * j + k) as f64);
+        module.forward(data.into_dyn());
+        module.backward(ArrayD::ones(vec![2, 1, 2, 2])).unwrap();
+
+        // The normalized input sums to zero, and the gradient is averaged over the batch
+        let grad_weight = module.0.grad_weight.clone();
+        let grad_bias = module.0.grad_bias.clone();
+        let expected_weight = array![0.0].into_dyn();
+        let expected_bias = array![4.0].into_dyn();
+        assert_array_eq!(grad_weight, expected_weight);
+        assert_array_eq!(grad_bias, expected_bias);
+    }
+
+    #[test]
+    fn buffers() {
+        let mut module: Sequential =
+            sequential!(Linear::new(3, 2), BatchNorm1d::new(2).momentum(1.0));
+        let names: Vec<_> = module.parameters().iter().map(|p| p.name).collect();
+        assert_eq!(vec!["0.weight", "0.bias", "1.weight", "1.bias"], names);
+
+        module.forward(Array::from_shape_fn((4, 3), |(i, j)| (i * j) as f64).into_dyn());
+        let state_dict = module.state_dict();
+        assert_eq!(
+            vec![
+                "0.bias",
+                "0.weight",
+                "1.bias",
+                "1.running_mean",
+                "1.running_var",
+                "1.weight"
+            ],
+            state_dict.keys().collect::<Vec<_>>()
+        );
+
+        let mut other: Sequential = sequential!(Linear::new(3, 2), BatchNorm1d::new(2));
+        other.load_state_dict(&state_dict).unwrap();
+        assert_eq!(state_dict, other.state_dict());
+
+        let mut partial = state_dict.clone();
+        partial.remove("1.running_var");
+        assert!(matches!(
+            other.load_state_dict(&partial),
+            Err(crate::Error::StateDictKeys { missing, .. }) if missing == ["1.running_var"]
+        ));
+    }
+
+    #[test]
+    fn output_shape() {
+        let module = BatchNorm2d::<f64>::new(3);
+        assert_eq!(
+            vec![8, 3, 4, 4],
+            module.output_shape(&[8, 3, 4, 4]).unwrap()
+        );
+        assert!(module.output_shape(&[8, 3, 4]).is_err());
+        assert!(module.output_shape(&[8, 2, 4, 4]).is_err());
+
+        let module = BatchNorm1d::<f64>::new(3);
+        assert!(module.output_shape(&[8, 3]).is_ok());
+        assert!(module.output_shape(&[8, 3, 5]).is_ok());
+    }
+
+    #[test]
+    fn layer_norm() {
+        let mut module = LayerNorm::new(&[2]);
+        let input = array![[[1.0, 3.0], [-2.0, -2.0]]].into_dyn();
+        let result = module.forward(input);
+        let expected = array![[[-1.0, 1.0], [0.0, 0.0]]].into_dyn();
+        assert_array_eq!(result, expected, 1e-4);
+
+        let mut module = LayerNorm::new(&[2, 2]);
+        module.affine.as_mut().unwrap().weight = array![[0.5, 2.0], [-1.0, 3.0]].into_dyn();
+        module.affine.as_mut().unwrap().bias = Some(array![[1.0, 0.0], [0.0, -1.0]].into_dyn());
+        check_gradients(&mut module, &data());
+        check_gradients(&mut LayerNorm::new(&[2]).elementwise_affine(false), &data());
+
+        assert!(module.output_shape(&[4, 2, 2]).is_ok());
+        assert!(module.output_shape(&[2, 2]).is_err());
+        assert!(module.output_shape(&[4, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn group_norm() {
+        let mut module = GroupNorm::new(1, 2);
+        let result = module.forward(array![[[1.0, 2.0], [3.0, 4.0]]].into_dyn());
+        let expected = array![[[-1.341641, -0.447214], [0.447214, 1.341641]]].into_dyn();
+        assert_array_eq!(result, expected, 1e-4);
+
+        let data = Array::from_shape_fn((2, 4, 3), |(i, j, k)| {
+            ((i * 5 + j * 3 + k * 7) % 13) as f64 - j as f64
+        })
+        .into_dyn();
+        let mut module = GroupNorm::new(2, 4);
+        module.affine.as_mut().unwrap().weight = array![0.5, 2.0, -1.0, 3.0].into_dyn();
+        module.affine.as_mut().unwrap().bias = Some(array![1.0, 0.0, 0.5, -1.0].into_dyn());
+        check_gradients(&mut module, &data);
+        check_gradients(&mut GroupNorm::new(4, 4).affine(false), &data);
+
+        assert!(module.output_shape(&[2, 4, 5, 5]).is_ok());
+        assert!(module.output_shape(&[2, 3, 5]).is_err());
+    }
+
+    #[test]
+    fn rms_norm() {
+        let mut module = RMSNorm::new(&[2]);
+        let result = module.forward(array![[3.0, 4.0], [0.0, -1.0]].into_dyn());
+        let rms = 12.5f64.sqrt();
+        let expected = array![[3.0 / rms, 4.0 / rms], [0.0, -2f64.sqrt()]].into_dyn();
+        assert_array_eq!(result, expected, 1e-4);
+        assert_eq!(1, module.parameters().iter().count());
+
+        let mut module = RMSNorm::new(&[2, 2]).eps(1e-6);
+        module.affine.as_mut().unwrap().weight = array![[0.5, 2.0], [-1.0, 3.0]].into_dyn();
+        check_gradients(&mut module, &data());
+        check_gradients(&mut RMSNorm::new(&[2]).elementwise_affine(false), &data());
+    }
+
+    #[test]
+    fn sequential() {
+        let mut module: Sequential = sequential!(
+            Linear::new(3, 4),
+            LayerNorm::new(&[4]),
+            RMSNorm::new(&[4]),
+            GroupNorm::new(2, 4)
+        );
+        let names: Vec<_> = module.parameters().iter().map(|p| p.name).collect();
+        assert_eq!(
+            vec!["0.weight", "0.bias", "1.weight", "1.bias", "2.weight", "3.weight", "3.bias"],
+            names
+        );
+        let summary = module.summary(&[8, 3]).unwrap();
+        assert_eq!(16 + 8 + 4 + 8, summary.num_parameters());
+
+        let output = module.forward(ArrayD::ones(vec![8, 3]));
+        assert_eq!(&[8, 4], output.shape());
+        let grad = module.backward(ArrayD::ones(vec![8, 4])).unwrap();
+        assert_eq!(&[8, 3], grad.shape());
+    }
+}
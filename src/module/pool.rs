@@ -0,0 +1,513 @@
+use crate::module::conv::{checked_output_len, input_4d, output_len, pad, unpad, window};
+use crate::module::{incompatible, saved, Module, Parameters};
+use crate::{Float, Result};
+use ndarray::prelude::*;
+use ndarray::{Slice, Zip};
+use std::marker::PhantomData;
+
+/// Sliding window shared by the pooling modules, given as `(height, width)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pool2d {
+    kernel_size: (usize, usize),
+    stride: (usize, usize),
+    padding: (usize, usize),
+}
+
+impl Pool2d {
+    #[inline]
+    fn new(kernel_size: (usize, usize), stride: (usize, usize), padding: (usize, usize)) -> Self {
+        assert!(
+            kernel_size.0 > 0 && kernel_size.1 > 0,
+            "Kernel size must be positive"
+        );
+        assert!(stride.0 > 0 && stride.1 > 0, "Stride must be positive");
+        assert!(
+            2 * padding.0 <= kernel_size.0 && 2 * padding.1 <= kernel_size.1,
+            "Padding must be at most half the kernel size"
+        );
+        Self {
+            kernel_size,
+            stride,
+            padding,
+        }
+    }
+
+    #[inline]
+    fn output_size(&self, height: usize, width: usize) -> (usize, usize) {
+        (
+            output_len(height, self.kernel_size.0, self.stride.0, self.padding.0, 1),
+            output_len(width, self.kernel_size.1, self.stride.1, self.padding.1, 1),
+        )
+    }
+
+    /// Output shape of the `layer` using the window, see [`Module::output_shape`].
+    fn output_shape(&self, layer: &str, input_shape: &[usize]) -> Result<Vec<usize>> {
+        let [n, c, h, w] = input_4d(layer, input_shape)?;
+        let (kh, kw) = self.kernel_size;
+        let (oh, ow) = checked_output_len(h, kh, self.stride.0, self.padding.0, 1)
+            .zip(checked_output_len(w, kw, self.stride.1, self.padding.1, 1))
+            .ok_or_else(|| {
+                let reason = format!(
+                    "window of size {:?} does not fit the padded input",
+                    (kh, kw)
+                );
+                incompatible(layer, input_shape, reason)
+            })?;
+        Ok(vec![n, c, oh, ow])
+    }
+
+    /// Slices of the padded input read by every kernel element, with its index in the kernel.
+    #[inline]
+    fn windows(&self, out: (usize, usize)) -> impl Iterator<Item = (usize, (Slice, Slice))> + '_ {
+        let (kh, kw) = self.kernel_size;
+        (0..kh * kw).map(move |k| (k, window((k / kw, k % kw), out, self.stride)))
+    }
+}
+
+#[inline]
+fn into_4d<F>(input: ArrayD<F>, name: &str) -> Array4<F> {
+    input.into_dimensionality().unwrap_or_else(|_| {
+        panic!("{name} expects an input of shape (batch_size, channels, height, width)")
+    })
+}
+
+/// Maximum over sliding windows of an input of shape `(batch_size, channels, height, width)`.
+/// Padded positions are never selected.
+#[derive(Debug)]
+pub struct MaxPool2d<F = f64> {
+    pool: Pool2d,
+
+    prev_input_shape: Option<Vec<usize>>,
+    /// Index in the kernel of the maximum of every window
+    prev_argmax: Option<Array4<usize>>,
+    float: PhantomData<F>,
+}
+
+impl<F> MaxPool2d<F> {
+    #[inline]
+    #[must_use]
+    pub fn new_with_options(
+        kernel_size: (usize, usize),
+        stride: (usize, usize),
+        padding: (usize, usize),
+    ) -> Self {
+        Self {
+            pool: Pool2d::new(kernel_size, stride, padding),
+            prev_input_shape: None,
+            prev_argmax: None,
+            float: PhantomData,
+        }
+    }
+
+    /// Non-overlapping square windows, the stride being equal to the kernel size.
+    #[inline]
+    #[must_use]
+    pub fn new(kernel_size: usize) -> Self {
+        let kernel_size = (kernel_size, kernel_size);
+        Self::new_with_options(kernel_size, kernel_size, (0, 0))
+    }
+}
+
+impl<F: Float> Module<F> for MaxPool2d<F> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let input = into_4d(input, "MaxPool2d");
+        let (n, c, h, w) = input.dim();
+        let out = self.pool.output_size(h, w);
+        let padded = pad(input.view(), self.pool.padding, F::neg_infinity());
+
+        let mut output = Array4::from_elem((n, c, out.0, out.1), F::neg_infinity());
+        let mut argmax = Array4::zeros(output.raw_dim());
+        for (k, (rows, cols)) in self.pool.windows(out) {
+            Zip::from(&mut output)
+                .and(&mut argmax)
+                .and(padded.slice(s![.., .., rows, cols]))
+                .for_each(|max, index, &x| {
+                    if x > *max {
+                        *max = x;
+                        *index = k;
+                    }
+                });
+        }
+
+        self.prev_input_shape = Some(vec![n, c, h, w]);
+        self.prev_argmax = Some(argmax);
+        output.into_dyn()
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let shape = saved(&mut self.prev_input_shape, "MaxPool2d")?;
+        let argmax = saved(&mut self.prev_argmax, "MaxPool2d")?;
+        let gradient = into_4d(gradient, "MaxPool2d");
+        let (ph, pw) = self.pool.padding;
+        let out = (argmax.shape()[2], argmax.shape()[3]);
+
+        let mut padded = Array4::zeros((shape[0], shape[1], shape[2] + 2 * ph, shape[3] + 2 * pw));
+        for (k, (rows, cols)) in self.pool.windows(out) {
+            Zip::from(padded.slice_mut(s![.., .., rows, cols]))
+                .and(&argmax)
+                .and(&gradient)
+                .for_each(|grad, &index, &g| {
+                    if index == k {
+                        *grad += g;
+                    }
+                });
+        }
+        Ok(unpad(padded, self.pool.padding).into_dyn())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        self.pool.output_shape("MaxPool2d", input_shape)
+    }
+}
+
+/// Mean over sliding windows of an input of shape `(batch_size, channels, height, width)`.
+/// Padded positions count as zeros.
+#[derive(Debug)]
+pub struct AvgPool2d<F = f64> {
+    pool: Pool2d,
+
+    prev_input_shape: Option<Vec<usize>>,
+    float: PhantomData<F>,
+}
+
+impl<F> AvgPool2d<F> {
+    #[inline]
+    #[must_use]
+    pub fn new_with_options(
+        kernel_size: (usize, usize),
+        stride: (usize, usize),
+        padding: (usize, usize),
+    ) -> Self {
+        Self {
+            pool: Pool2d::new(kernel_size, stride, padding),
+            prev_input_shape: None,
+            float: PhantomData,
+        }
+    }
+
+    /// Non-overlapping square windows, the stride being equal to the kernel size.
+    #[inline]
+    #[must_use]
+    pub fn new(kernel_size: usize) -> Self {
+        let kernel_size = (kernel_size, kernel_size);
+        Self::new_with_options(kernel_size, kernel_size, (0, 0))
+    }
+}
+
+impl<F: Float> AvgPool2d<F> {
+    #[inline]
+    fn window_size(&self) -> F {
+        F::from_usize(self.pool.kernel_size.0 * self.pool.kernel_size.1)
+    }
+}
+
+impl<F: Float> Module<F> for AvgPool2d<F> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let input = into_4d(input, "AvgPool2d");
+        let (n, c, h, w) = input.dim();
+        let out = self.pool.output_size(h, w);
+        let padded = pad(input.view(), self.pool.padding, F::zero());
+
+        let mut output = Array4::zeros((n, c, out.0, out.1));
+        for (_, (rows, cols)) in self.pool.windows(out) {
+            output += &padded.slice(s![.., .., rows, cols]);
+        }
+
+        self.prev_input_shape = Some(vec![n, c, h, w]);
+        (output / self.window_size()).into_dyn()
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let shape = saved(&mut self.prev_input_shape, "AvgPool2d")?;
+        let gradient = into_4d(gradient, "AvgPool2d") / self.window_size();
+        let (ph, pw) = self.pool.padding;
+        let out = (gradient.shape()[2], gradient.shape()[3]);
+
+        let mut padded = Array4::zeros((shape[0], shape[1], shape[2] + 2 * ph, shape[3] + 2 * pw));
+        for (_, (rows, cols)) in self.pool.windows(out) {
+            let mut target = padded.slice_mut(s![.., .., rows, cols]);
+            target += &gradient;
+        }
+        Ok(unpad(padded, self.pool.padding).into_dyn())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        self.pool.output_shape("AvgPool2d", input_shape)
+    }
+}
+
+/// Mean over windows chosen so that the output of an input of shape
+/// `(batch_size, channels, height, width)` always has the requested `(height, width)`.
+#[derive(Debug)]
+pub struct AdaptiveAvgPool2d<F = f64> {
+    output_size: (usize, usize),
+
+    prev_input_shape: Option<Vec<usize>>,
+    float: PhantomData<F>,
+}
+
+impl<F> AdaptiveAvgPool2d<F> {
+    #[inline]
+    #[must_use]
+    pub fn new(output_size: (usize, usize)) -> Self {
+        assert!(
+            output_size.0 > 0 && output_size.1 > 0,
+            "Output size must be positive"
+        );
+        Self {
+            output_size,
+            prev_input_shape: None,
+            float: PhantomData,
+        }
+    }
+
+    /// Range of the input averaged into the output position `index` along an axis.
+    #[inline]
+    fn range(index: usize, input: usize, output: usize) -> std::ops::Range<usize> {
+        let start = index * input / output;
+        let end = ((index + 1) * input).div_ceil(output);
+        start..end
+    }
+
+    #[inline]
+    fn windows(
+        &self,
+        height: usize,
+        width: usize,
+    ) -> impl Iterator<Item = ((usize, usize), Slice, Slice)> + '_ {
+        let (oh, ow) = self.output_size;
+        ndarray::indices((oh, ow)).into_iter().map(move |(y, x)| {
+            (
+                (y, x),
+                Self::range(y, height, oh).into(),
+                Self::range(x, width, ow).into(),
+            )
+        })
+    }
+}
+
+impl<F: Float> Module<F> for AdaptiveAvgPool2d<F> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
+        let input = into_4d(input, "AdaptiveAvgPool2d");
+        let (n, c, h, w) = input.dim();
+
+        let mut output = Array4::zeros((n, c, self.output_size.0, self.output_size.1));
+        for ((y, x), rows, cols) in self.windows(h, w) {
+            let region = input.slice(s![.., .., rows, cols]);
+            let count = F::from_usize(region.shape()[2] * region.shape()[3]);
+            output
+                .slice_mut(s![.., .., y, x])
+                .assign(&(region.sum_axis(Axis(3)).sum_axis(Axis(2)) / count));
+        }
+
+        self.prev_input_shape = Some(vec![n, c, h, w]);
+        output.into_dyn()
+    }
+
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
+        let shape = saved(&mut self.prev_input_shape, "AdaptiveAvgPool2d")?;
+        let gradient = into_4d(gradient, "AdaptiveAvgPool2d");
+
+        let mut result = Array4::zeros((shape[0], shape[1], shape[2], shape[3]));
+        for ((y, x), rows, cols) in self.windows(shape[2], shape[3]) {
+            let mut region = result.slice_mut(s![.., .., rows, cols]);
+            let count = F::from_usize(region.shape()[2] * region.shape()[3]);
+            region += &(&gradient.slice(s![.., .., y..=y, x..=x]) / count);
+        }
+        Ok(result.into_dyn())
+    }
+
+    fn parameters(&mut self) -> Parameters<'_, F> {
+        Parameters::new(0)
+    }
+
+    #[inline]
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        let [n, c, _, _] = input_4d("AdaptiveAvgPool2d", input_shape)?;
+        Ok(vec![n, c, self.output_size.0, self.output_size.1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+
+    /// (height, width) -> (1, 1, height, width)
+    fn image(array: Array2<f64>) -> ArrayD<f64> {
+        let (h, w) = array.dim();
+        array.into_shape((1, 1, h, w)).unwrap().into_dyn()
+    }
+
+    fn input() -> ArrayD<f64> {
+        image(array![
+            [1.0, 2.0, -1.0, 0.0],
+            [4.0, 3.0, 5.0, -2.0],
+            [0.0, -1.0, 2.0, 2.0],
+            [6.0, 1.0, 0.5, 3.0]
+        ])
+    }
+
+    #[test]
+    fn max_pool() {
+        let mut module = MaxPool2d::new(2);
+        let result = module.forward(input());
+        assert_array_eq!(result, image(array![[4.0, 5.0], [6.0, 3.0]]));
+
+        let result = module
+            .backward(image(array![[1.0, 2.0], [3.0, 4.0]]))
+            .unwrap();
+        let expected = image(array![
+            [0.0, 0.0, 0.0, 0.0],
+            [1.0, 0.0, 2.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [3.0, 0.0, 0.0, 4.0]
+        ]);
+        assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn max_pool_overlapping_with_padding() {
+        let mut module = MaxPool2d::new_with_options((3, 3), (2, 2), (1, 1));
+        let result = module.forward(input());
+        assert_array_eq!(result, image(array![[4.0, 5.0], [6.0, 5.0]]));
+
+        // The 5 is the maximum of two windows, so it gets both gradients
+        let result = module
+            .backward(ArrayD::<f64>::ones(vec![1, 1, 2, 2]))
+            .unwrap();
+        let expected = image(array![
+            [0.0, 0.0, 0.0, 0.0],
+            [1.0, 0.0, 2.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0]
+        ]);
+        assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn avg_pool() {
+        let mut module = AvgPool2d::new(2);
+        let result = module.forward(input());
+        assert_array_eq!(result, image(array![[2.5, 0.5], [1.5, 1.875]]));
+
+        let result = module
+            .backward(image(array![[4.0, 8.0], [0.0, -4.0]]))
+            .unwrap();
+        let expected = image(array![
+            [1.0, 1.0, 2.0, 2.0],
+            [1.0, 1.0, 2.0, 2.0],
+            [0.0, 0.0, -1.0, -1.0],
+            [0.0, 0.0, -1.0, -1.0]
+        ]);
+        assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn avg_pool_with_padding() {
+        let mut module = AvgPool2d::new_with_options((2, 2), (2, 2), (1, 1));
+        let data = ArrayD::<f64>::ones(vec![1, 1, 2, 2]);
+        let result = module.forward(data);
+        assert_array_eq!(result, image(array![[0.25, 0.25], [0.25, 0.25]]));
+
+        let result = module
+            .backward(ArrayD::<f64>::ones(vec![1, 1, 2, 2]))
+            .unwrap();
+        assert_array_eq!(result, ArrayD::from_elem(vec![1, 1, 2, 2], 0.25));
+    }
+
+    #[test]
+    fn adaptive_avg_pool() {
+        // Windows of rows [0, 2), [1, 3), [2, 4) and columns [0, 2), [2, 4)
+        let mut module = AdaptiveAvgPool2d::new((3, 2));
+        let result = module.forward(input());
+        let expected = image(array![[2.5, 0.5], [1.5, 1.75], [1.5, 1.875]]);
+        assert_array_eq!(result, expected);
+
+        let result = module
+            .backward(ArrayD::<f64>::from_elem(vec![1, 1, 3, 2], 4.0))
+            .unwrap();
+        let expected = image(array![
+            [1.0, 1.0, 1.0, 1.0],
+            [2.0, 2.0, 2.0, 2.0],
+            [2.0, 2.0, 2.0, 2.0],
+            [1.0, 1.0, 1.0, 1.0]
+        ]);
+        assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn adaptive_avg_pool_upsampling() {
+        // Windows of rows and columns [0, 1), [0, 2), [1, 2)
+        let mut module = AdaptiveAvgPool2d::new((3, 3));
+        let result = module.forward(image(array![[1.0, 2.0], [3.0, 4.0]]));
+        let expected = image(array![[1.0, 1.5, 2.0], [2.0, 2.5, 3.0], [3.0, 3.5, 4.0]]);
+        assert_array_eq!(result, expected);
+
+        let result = module
+            .backward(ArrayD::<f64>::ones(vec![1, 1, 3, 3]))
+            .unwrap();
+        assert_array_eq!(result, ArrayD::from_elem(vec![1, 1, 2, 2], 2.25));
+    }
+
+    #[test]
+    fn global_avg_pool() {
+        let mut module = AdaptiveAvgPool2d::new((1, 1));
+        let data = Array::range(0.0f32, 24.0, 1.0)
+            .into_shape((2, 3, 2, 2))
+            .unwrap()
+            .into_dyn();
+        let result = module.forward(data);
+        let expected = array![[1.5f32, 5.5, 9.5], [13.5, 17.5, 21.5]]
+            .into_shape((2, 3, 1, 1))
+            .unwrap()
+            .into_dyn();
+        assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn lenet() {
+        use crate::module::{Conv2d, Flatten, Linear, ReLU, SafeModule, Sequential};
+        use crate::{safe, sequential};
+
+        let module: Sequential = sequential!(
+            Conv2d::new_with_options(
+                1,
+                6,
+                (5, 5),
+                crate::module::Conv2dOptions::default().padding(2)
+            ),
+            ReLU::new(),
+            MaxPool2d::new(2),
+            Conv2d::new(6, 16, 5),
+            ReLU::new(),
+            AvgPool2d::new(2),
+            Flatten::new(),
+            Linear::new(16 * 5 * 5, 10),
+        );
+        let shape = Module::<f64>::output_shape(&module, &[2, 1, 28, 28]).unwrap();
+        assert_eq!(vec![2, 10], shape);
+        let result = MaxPool2d::<f64>::new(2).output_shape(&[2, 1, 1, 3]);
+        assert!(matches!(
+            result,
+            Err(crate::Error::IncompatibleShape { .. })
+        ));
+        let module = safe!(module);
+
+        let (module, result) = module.forward(ArrayD::<f64>::ones(vec![2, 1, 28, 28]));
+        assert_eq!(&[2, 10], result.shape());
+
+        let (_module, result) = module.backward(ArrayD::<f64>::ones(vec![2, 10]));
+        assert_eq!(&[2, 1, 28, 28], result.shape());
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 use crate::module::Module;
+use crate::Float;
 use std::marker::PhantomData;
 use std::mem::ManuallyDrop;
 
@@ -46,7 +47,7 @@ impl<M, S, E> SafeModule<M, S, E> {
     }
 }
 
-impl<M: Module> SafeModule<M, Forward, Train> {
+impl<M> SafeModule<M, Forward, Train> {
     #[inline]
     #[must_use]
     pub fn new(module: M) -> Self {
@@ -58,45 +59,62 @@ impl<M: Module> SafeModule<M, Forward, Train> {
     }
 }
 
-impl<M: Module, E> SafeModule<M, Forward, E> {
-    /// (batch_size, input_size) -> (batch_size, output_size)
+impl<M, E> SafeModule<M, Forward, E> {
+    /// (batch_size, *input_shape) -> (batch_size, *output_shape)
     #[inline]
-    pub fn forward(mut self, input: Array2<f64>) -> (SafeModule<M, Backward, E>, Array2<f64>) {
+    pub fn forward<F>(mut self, input: ArrayD<F>) -> (SafeModule<M, Backward, E>, ArrayD<F>)
+    where
+        F: Float,
+        M: Module<F>,
+    {
         let pred = self.module.forward(input);
         let new_state = self.new_state();
         (new_state, pred)
     }
 }
 
-impl<M: Module, E> SafeModule<M, Backward, E> {
-    /// (batch_size, output_size) -> (batch_size, input_size)
+impl<M, E> SafeModule<M, Backward, E> {
+    /// (batch_size, *output_shape) -> (batch_size, *input_shape)
     #[inline]
-    pub fn backward(mut self, gradient: Array2<f64>) -> (SafeModule<M, Forward, E>, Array2<f64>) {
-        let grad = self.module.backward(gradient);
+    pub fn backward<F>(mut self, gradient: ArrayD<F>) -> (SafeModule<M, Forward, E>, ArrayD<F>)
+    where
+        F: Float,
+        M: Module<F>,
+    {
+        // The forward pass is guaranteed by the state
+        let grad = self
+            .module
+            .backward(gradient)
+            .expect("SafeModule in the Backward state");
         let new_state = self.new_state();
         (new_state, grad)
     }
 }
 
-impl<M: Module, S> SafeModule<M, S, Train> {
+impl<M, S> SafeModule<M, S, Train> {
     #[inline]
-    pub fn eval(mut self) -> SafeModule<M, S, Evaluation> {
+    pub fn eval<F: Float>(mut self) -> SafeModule<M, S, Evaluation>
+    where
+        M: Module<F>,
+    {
         self.module.eval();
         self.new_mode()
     }
 }
 
-impl<M: Module, S> SafeModule<M, S, Evaluation> {
+impl<M, S> SafeModule<M, S, Evaluation> {
     #[inline]
-    pub fn train(mut self) -> SafeModule<M, S, Train> {
+    pub fn train<F: Float>(mut self) -> SafeModule<M, S, Train>
+    where
+        M: Module<F>,
+    {
         self.module.train();
         self.new_mode()
     }
 }
 
-impl<M: Module> From<M> for SafeModule<M, Forward, Train> {
+impl<M> From<M> for SafeModule<M, Forward, Train> {
     #[inline]
-    #[must_use]
     fn from(module: M) -> Self {
         Self::new(module)
     }
@@ -122,23 +140,23 @@ mod tests {
 
     #[test]
     fn test_macro() {
-        let _relu = safe!(ReLU());
-        let _linear = safe!(Linear(10, 20,));
+        let _relu: SafeModule<ReLU, _, _> = safe!(ReLU());
+        let _linear: SafeModule<Linear, _, _> = safe!(Linear(10, 20,));
 
-        let _relu = safe!(ReLU::new());
-        let _linear = safe!(Linear::new(10, 20));
+        let _relu = safe!(ReLU::<f32>::new());
+        let _linear = safe!(Linear::<f32>::new(10, 20));
     }
 
     #[test]
     fn test_state() {
         let relu = safe!(ReLU());
-        let (relu, _) = relu.forward(Array::ones((2, 3)));
-        let (_relu, _) = relu.backward(Array::ones((2, 3)));
+        let (relu, _) = relu.forward(ArrayD::<f64>::ones(vec![2, 3]));
+        let (_relu, _) = relu.backward(ArrayD::ones(vec![2, 3]));
     }
 
     #[test]
     fn test_mode() {
-        let linear = safe!(Linear(10, 20,));
+        let linear: SafeModule<Linear, _, _> = safe!(Linear(10, 20,));
         let linear = linear.eval();
         let _linear = linear.train();
     }
@@ -0,0 +1,149 @@
+use super::StateDict;
+use crate::{Error, Float, Result};
+use ndarray::prelude::*;
+use safetensors::tensor::{Dtype, SafeTensorError, SafeTensors, TensorView};
+use std::path::Path;
+
+impl From<SafeTensorError> for Error {
+    #[inline]
+    fn from(error: SafeTensorError) -> Self {
+        Error::Format(error.to_string())
+    }
+}
+
+/// Reads little endian floats of `N` bytes, the layout of safetensors data.
+#[inline]
+fn decode<F: Float, const N: usize>(data: &[u8], from_le_bytes: fn([u8; N]) -> f64) -> Vec<F> {
+    data.chunks_exact(N)
+        .map(|bytes| F::from_f64(from_le_bytes(bytes.try_into().unwrap())))
+        .collect()
+}
+
+/// Import and export of the [safetensors](https://github.com/huggingface/safetensors) format,
+/// used to exchange weights with PyTorch. Arrays are written with the float type of the state
+/// dict, use [`StateDict::cast`] to change it.
+impl<F: Float> StateDict<F> {
+    pub fn to_safetensors(&self) -> Result<Vec<u8>> {
+        let size = std::mem::size_of::<F>();
+        let dtype = match size {
+            4 => Dtype::F32,
+            _ => Dtype::F64,
+        };
+
+        let data: Vec<_> = self
+            .iter()
+            .map(|(name, array)| {
+                let mut bytes = Vec::with_capacity(array.len() * size);
+                for &x in array.iter() {
+                    let x = x.to_f64().unwrap();
+                    match dtype {
+                        Dtype::F32 => bytes.extend_from_slice(&(x as f32).to_le_bytes()),
+                        _ => bytes.extend_from_slice(&x.to_le_bytes()),
+                    }
+                }
+                (name, array.shape().to_vec(), bytes)
+            })
+            .collect();
+        let views = data
+            .iter()
+            .map(|(name, shape, bytes)| Ok((*name, TensorView::new(dtype, shape.clone(), bytes)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(safetensors::serialize(views, &None)?)
+    }
+
+    /// Converts `F16`, `BF16`, `F32` and `F64` tensors to the float type of the state dict.
+    pub fn from_safetensors(bytes: &[u8]) -> Result<Self> {
+        SafeTensors::deserialize(bytes)?
+            .tensors()
+            .into_iter()
+            .map(|(name, view)| {
+                let data = match view.dtype() {
+                    Dtype::F64 => decode(view.data(), f64::from_le_bytes),
+                    Dtype::F32 => decode(view.data(), |b| f32::from_le_bytes(b) as f64),
+                    Dtype::BF16 => decode(view.data(), |[lo, hi]| {
+                        f32::from_le_bytes([0, 0, lo, hi]) as f64
+                    }),
+                    Dtype::F16 => decode(view.data(), |b| f16_to_f64(u16::from_le_bytes(b))),
+                    dtype => {
+                        return Err(Error::Format(format!(
+                            "{name} has unsupported dtype {dtype:?}"
+                        )))
+                    }
+                };
+                let array = ArrayD::from_shape_vec(view.shape(), data).unwrap();
+                Ok((name, array))
+            })
+            .collect()
+    }
+
+    #[inline]
+    pub fn save_safetensors<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Ok(std::fs::write(path, self.to_safetensors()?)?)
+    }
+
+    #[inline]
+    pub fn load_safetensors<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_safetensors(&std::fs::read(path)?)
+    }
+}
+
+/// IEEE 754 half precision, as PyTorch exports `torch.float16`.
+fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits >> 15 == 1 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f64;
+    sign * match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::Module;
+    use crate::{assert_array_eq, sequential, Linear, ReLU, Sequential};
+
+    #[test]
+    fn round_trip() {
+        let mut module: Sequential = sequential!(Linear(3, 4), ReLU(), Linear(4, 2));
+        let state_dict = module.state_dict();
+
+        let bytes = state_dict.to_safetensors().unwrap();
+        assert_eq!(state_dict, StateDict::from_safetensors(&bytes).unwrap());
+
+        // Exported as f32, as usual for PyTorch
+        let bytes = state_dict.cast::<f32>().to_safetensors().unwrap();
+        let loaded = StateDict::<f64>::from_safetensors(&bytes).unwrap();
+        assert_eq!(&[4, 3], loaded.get("0.weight").unwrap().shape());
+        let result = loaded.get("2.bias").unwrap().clone();
+        let expected = state_dict.get("2.bias").unwrap().clone();
+        assert_array_eq!(result, expected, 1e-6);
+        module.load_state_dict(&loaded).unwrap();
+    }
+
+    #[test]
+    fn pytorch_file() {
+        // As written by safetensors.torch.save_file({"weight": torch.tensor([[1., -2.]]),
+        //     "bias": torch.tensor([0.5], dtype=torch.float16)}, path)
+        let header = br#"{"bias":{"dtype":"F16","shape":[1],"data_offsets":[0,2]},"weight":{"dtype":"F32","shape":[1,2],"data_offsets":[2,10]}}"#;
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header);
+        bytes.extend_from_slice(&0x3800u16.to_le_bytes());
+        bytes.extend_from_slice(&1f32.to_le_bytes());
+        bytes.extend_from_slice(&(-2f32).to_le_bytes());
+
+        let state_dict = StateDict::<f32>::from_safetensors(&bytes).unwrap();
+        let bias = state_dict.get("bias").unwrap().clone();
+        assert_array_eq!(bias, array![0.5f32].into_dyn());
+        let weight = state_dict.get("weight").unwrap().clone();
+        assert_array_eq!(weight, array![[1f32, -2.0]].into_dyn());
+
+        assert!(matches!(
+            StateDict::<f32>::from_safetensors(&bytes[..bytes.len() - 1]),
+            Err(Error::Format(_))
+        ));
+    }
+}
@@ -1,32 +1,61 @@
 use std::fmt::Debug;
 
-use crate::module::Module;
+use crate::module::{Module, Summary};
+use crate::{Error, Float, Result};
 use ndarray::prelude::*;
 
-use super::Parameters;
+use super::{Buffers, Parameters};
 
-pub trait ModuleDebug: Module + Debug {}
-impl<T: Module + Debug> ModuleDebug for T {}
+pub trait ModuleDebug<F: Float = f64>: Module<F> + Debug {
+    /// Name of the type of the module, without its path and generics, like `Linear`.
+    #[inline]
+    fn type_name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
+}
+impl<F: Float, T: Module<F> + Debug> ModuleDebug<F> for T {}
+
+/// Output shape and size of a layer of a [`Sequential`], see [`Sequential::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerShape {
+    /// Index of the layer
+    pub name: String,
+    /// See [`ModuleDebug::type_name`]
+    pub type_name: &'static str,
+    pub output_shape: Vec<usize>,
+    pub num_parameters: usize,
+    /// Parameters that are not frozen
+    pub num_trainable: usize,
+}
+
+#[derive(Debug)]
+pub struct Sequential<F = f64> {
+    layers: Vec<Box<dyn ModuleDebug<F>>>,
+}
 
-#[derive(Debug, Default)]
-pub struct Sequential {
-    layers: Vec<Box<dyn ModuleDebug>>,
+impl<F: Float> Default for Sequential<F> {
+    #[inline]
+    fn default() -> Self {
+        Self { layers: Vec::new() }
+    }
 }
 
-impl Sequential {
+impl<F: Float> Sequential<F> {
     #[inline]
     #[must_use]
-    pub fn new(layers: Vec<Box<dyn ModuleDebug>>) -> Sequential {
+    pub fn new(layers: Vec<Box<dyn ModuleDebug<F>>>) -> Self {
         Sequential { layers }
     }
 
     #[inline]
-    pub fn push<M: ModuleDebug + 'static>(&mut self, layer: M) {
+    pub fn push<M: ModuleDebug<F> + 'static>(&mut self, layer: M) {
         self.layers.push(Box::new(layer))
     }
 
     #[inline]
-    pub fn insert<M: ModuleDebug + 'static>(&mut self, index: usize, layer: M) {
+    pub fn insert<M: ModuleDebug<F> + 'static>(&mut self, index: usize, layer: M) {
         self.layers.insert(index, Box::new(layer))
     }
 
@@ -41,7 +70,7 @@ impl Sequential {
     }
 
     #[inline]
-    pub fn remove(&mut self, index: usize) -> Option<Box<dyn ModuleDebug>> {
+    pub fn remove(&mut self, index: usize) -> Option<Box<dyn ModuleDebug<F>>> {
         match index >= self.len() {
             true => None,
             false => Some(self.layers.remove(index)),
@@ -49,14 +78,65 @@ impl Sequential {
     }
 
     #[inline]
-    pub fn push_box(&mut self, layer: Box<dyn ModuleDebug>) {
+    pub fn push_box(&mut self, layer: Box<dyn ModuleDebug<F>>) {
         self.layers.push(layer)
     }
 
     #[inline]
-    pub fn insert_box(&mut self, index: usize, layer: Box<dyn ModuleDebug>) {
+    pub fn insert_box(&mut self, index: usize, layer: Box<dyn ModuleDebug<F>>) {
         self.layers.insert(index, layer)
     }
+
+    /// Checks that the layers can be chained for an input of shape `(batch_size, *input_shape)`
+    /// without running them, returning the output shape and number of parameters of every layer.
+    /// Fails with [`Error::IncompatibleShape`] at the first layer that cannot take the output of
+    /// the previous one.
+    pub fn validate(&mut self, input_shape: &[usize]) -> Result<Vec<LayerShape>> {
+        let mut shape = input_shape.to_vec();
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .map(|(i, layer)| {
+                shape = layer.output_shape(&shape).map_err(|e| at_layer(e, i))?;
+                Ok(LayerShape {
+                    name: i.to_string(),
+                    type_name: layer.type_name(),
+                    output_shape: shape.clone(),
+                    num_parameters: layer.num_parameters(),
+                    num_trainable: layer.parameters().trainable().map(|p| p.parm.len()).sum(),
+                })
+            })
+            .collect()
+    }
+
+    /// Table of the layers with their output shape and number of parameters for an input of
+    /// shape `(batch_size, *input_shape)`, to be printed. Fails like [`Sequential::validate`].
+    #[inline]
+    pub fn summary(&mut self, input_shape: &[usize]) -> Result<Summary> {
+        Ok(Summary::new::<F>(input_shape, self.validate(input_shape)?))
+    }
+}
+
+/// Adds the index of a layer to the path of the layer that cannot take its input.
+fn at_layer(error: Error, index: usize) -> Error {
+    match error {
+        Error::IncompatibleShape {
+            layer,
+            input,
+            reason,
+        } => {
+            let layer = match layer.ends_with(')') {
+                true => format!("{index}.{layer}"),
+                false => format!("{index} ({layer})"),
+            };
+            Error::IncompatibleShape {
+                layer,
+                input,
+                reason,
+            }
+        }
+        error => error,
+    }
 }
 
 #[macro_export]
@@ -73,20 +153,20 @@ macro_rules! sequential {
     );
 }
 
-impl Module for Sequential {
+impl<F: Float> Module<F> for Sequential<F> {
     #[inline]
-    fn forward(&mut self, input: Array2<f64>) -> Array2<f64> {
+    fn forward(&mut self, input: ArrayD<F>) -> ArrayD<F> {
         self.layers
             .iter_mut()
             .fold(input, |input, layer| layer.forward(input))
     }
 
     #[inline]
-    fn backward(&mut self, gradient: Array2<f64>) -> Array2<f64> {
+    fn backward(&mut self, gradient: ArrayD<F>) -> Result<ArrayD<F>> {
         self.layers
             .iter_mut()
             .rev()
-            .fold(gradient, |gradient, layer| layer.backward(gradient))
+            .try_fold(gradient, |gradient, layer| layer.backward(gradient))
     }
 
     #[inline]
@@ -99,14 +179,42 @@ impl Module for Sequential {
         self.layers.iter_mut().for_each(|layer| layer.eval())
     }
 
-    fn parameters(&mut self) -> Parameters<'_> {
+    fn parameters(&mut self) -> Parameters<'_, F> {
         let parms = self
             .layers
             .iter_mut()
-            .flat_map(|l| l.parameters().iter())
+            .enumerate()
+            .flat_map(|(i, l)| l.parameters().prefix(&i.to_string()).iter())
             .collect::<Vec<_>>();
         Parameters { parms }
     }
+
+    fn buffers(&mut self) -> Buffers<'_, F> {
+        let buffers = self
+            .layers
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, l)| l.buffers().prefix(&i.to_string()).iter())
+            .collect::<Vec<_>>();
+        Buffers { buffers }
+    }
+
+    fn output_shape(&self, input_shape: &[usize]) -> Result<Vec<usize>> {
+        self.layers
+            .iter()
+            .enumerate()
+            .try_fold(input_shape.to_vec(), |shape, (i, layer)| {
+                layer.output_shape(&shape).map_err(|e| at_layer(e, i))
+            })
+    }
+
+    fn children(&mut self) -> Vec<(String, &mut dyn Module<F>)> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .map(|(i, layer)| (i.to_string(), layer.as_mut() as &mut dyn Module<F>))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -114,11 +222,13 @@ mod tests {
     use super::*;
     use crate::module::activation::ReLU;
     use crate::module::linear::Linear;
+    use crate::module::{Conv2d, Flatten};
     use crate::Softmax;
+    use ndarray::Slice;
 
     #[test]
     fn test_macro() {
-        let _module = sequential!(
+        let _module: Sequential = sequential!(
             Linear(3, 10,),
             ReLU(),
             Linear(10, 100),
@@ -127,7 +237,7 @@ mod tests {
             Softmax(),
         );
 
-        let _module_2 = sequential!(
+        let _module_2: Sequential<f32> = sequential!(
             Linear::new(3, 10,),
             ReLU::new(),
             Linear::new(10, 100),
@@ -147,12 +257,22 @@ mod tests {
             Linear(100, 2),
             Softmax(),
         );
-        let data = array![[1.0, 2.0, 3.0], [-4.0, -5.0, -6.0]];
+        let data = array![[1.0, 2.0, 3.0], [-4.0, -5.0, -6.0]].into_dyn();
         let _result = module.forward(data.clone());
 
         // TODO: finish test
     }
 
+    #[test]
+    fn forward_f32() {
+        let mut module = sequential!(Linear(3, 10), ReLU(), Linear(10, 2), Softmax());
+        let data = array![[1.0f32, 2.0, 3.0], [-4.0, -5.0, -6.0]].into_dyn();
+        let result = module.forward(data);
+
+        assert_eq!(&[2, 2], result.shape());
+        crate::assert_array_eq!(result.sum_axis(Axis(1)), array![1.0f32, 1.0]);
+    }
+
     #[test]
     fn backward() {
         let mut module = sequential!(
@@ -163,11 +283,127 @@ mod tests {
             Linear(100, 2),
             Softmax(),
         );
-        let data = array![[1.0, 2.0, 3.0], [-4.0, -5.0, -6.0]];
+        let data = array![[1.0, 2.0, 3.0], [-4.0, -5.0, -6.0]].into_dyn();
         module.forward(data);
-        let backward_data = array![[1.0, 2.0], [3.0, -4.0]];
-        module.backward(backward_data);
+        let backward_data = array![[1.0, 2.0], [3.0, -4.0]].into_dyn();
+        module.backward(backward_data).unwrap();
 
         // TODO: finish test
     }
+
+    #[test]
+    fn conv() {
+        let mut module: Sequential = sequential!(
+            Conv2d(1, 4, 3),
+            ReLU(),
+            Conv2d(4, 8, 3),
+            Flatten(),
+            Linear(8 * 2 * 2, 2),
+        );
+        let data = ArrayD::ones(vec![3, 1, 6, 6]);
+        let result = module.forward(data);
+        assert_eq!(&[3, 2], result.shape());
+
+        let result = module.backward(ArrayD::ones(vec![3, 2])).unwrap();
+        assert_eq!(&[3, 1, 6, 6], result.shape());
+        let names: Vec<_> = module.parameters().iter().map(|p| p.name).collect();
+        assert_eq!(
+            vec!["0.weight", "0.bias", "2.weight", "2.bias", "4.weight", "4.bias"],
+            names
+        );
+    }
+
+    #[test]
+    fn accumulate_micro_batches() {
+        let mut module: Sequential =
+            sequential!(Conv2d(1, 2, 3), ReLU(), Flatten(), Linear(2 * 2 * 2, 3),);
+        let data = ArrayD::from_shape_fn(vec![8, 1, 4, 4], |i| (i[0] * 7 + i[2] * 3 + i[3]) as f64);
+        let data = data.mapv(|x| (x * 0.37).sin());
+        let target = ArrayD::from_shape_fn(vec![8, 3], |i| (i[0] + i[1]) as f64 * 0.1);
+
+        let pred = module.forward(data.clone());
+        module.backward(pred - &target).unwrap();
+        let expected: Vec<_> = module.parameters().iter().map(|p| p.grad.clone()).collect();
+
+        // Gradients of 4 micro-batches of 2, scaled like the loss of each micro-batch
+        module.zero_grad();
+        for i in 0..4 {
+            let range = Slice::from(2 * i..2 * (i + 1));
+            let pred = module.forward(data.slice_axis(Axis(0), range).to_owned());
+            let target = target.slice_axis(Axis(0), range).to_owned();
+            module.backward((pred - target) / 4.0).unwrap();
+        }
+        let grads: Vec<_> = module.parameters().iter().map(|p| p.grad.clone()).collect();
+        for (result, expected) in grads.into_iter().zip(expected) {
+            crate::assert_array_eq!(result, expected, 1e-12);
+        }
+    }
+
+    #[test]
+    fn validate() {
+        let mut module: Sequential = sequential!(
+            Conv2d(1, 4, 3),
+            ReLU(),
+            Flatten(),
+            Linear(4 * 26 * 26, 50),
+            Linear(50, 10),
+        );
+        let layers = module.validate(&[32, 1, 28, 28]).unwrap();
+        let shapes: Vec<_> = layers.iter().map(|l| l.output_shape.clone()).collect();
+        let expected = [
+            vec![32, 4, 26, 26],
+            vec![32, 4, 26, 26],
+            vec![32, 2704],
+            vec![32, 50],
+            vec![32, 10],
+        ];
+        assert_eq!(expected.to_vec(), shapes);
+        let sizes: Vec<_> = layers.iter().map(|l| l.num_parameters).collect();
+        assert_eq!(vec![40, 0, 0, 2704 * 50 + 50, 510], sizes);
+        assert_eq!("3", layers[3].name);
+
+        let head: Sequential = sequential!(Linear(50, 20), Linear(100, 10));
+        let mut module: Sequential = sequential!(Linear::new(784, 50), head);
+        let error = module.validate(&[32, 784]).unwrap_err();
+        assert_eq!(
+            "Layer 1.1 (Linear) cannot take an input of shape [32, 20]: expected 100 input \
+             features, found 20",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn named_modules() {
+        let head: Sequential = sequential!(Linear(3, 4), Linear(4, 1));
+        let mut module: Sequential = sequential!(Linear::new(2, 3), ReLU::new(), head);
+        assert_eq!(6 + 3 + 12 + 4 + 4 + 1, module.num_parameters());
+
+        let children: Vec<_> = module.children().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(vec!["0", "1", "2"], children);
+
+        let mut modules = Vec::new();
+        module.named_modules(&mut |name, module| {
+            modules.push((name.to_string(), module.num_parameters()));
+            // Selectively initialises the head
+            if name == "2" {
+                module
+                    .named_parameters()
+                    .into_iter()
+                    .for_each(|p| p.parm.fill(0.0));
+            }
+        });
+        let expected = [("0", 9), ("1", 0), ("2", 21), ("2.0", 16), ("2.1", 5)];
+        let expected: Vec<_> = expected.map(|(n, c)| (n.to_string(), c)).into();
+        assert_eq!(expected, modules);
+
+        let head_sum: f64 = module
+            .named_parameters()
+            .into_iter()
+            .filter(|p| p.name.starts_with("2."))
+            .map(|p| p.parm.sum())
+            .sum();
+        assert_eq!(0.0, head_sum);
+        assert_eq!(&[4, 3], module.named_parameters()[2].parm.shape());
+        assert_eq!("2.0.weight", module.named_parameters()[2].name);
+    }
 }
@@ -0,0 +1,346 @@
+use crate::{Error, Float, Result};
+use ndarray::prelude::*;
+use std::collections::btree_map::{self, BTreeMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"RSTORCH\0";
+const VERSION: u32 = 1;
+
+/// Arrays of a module by name, e.g. `0.weight` for the weight of the first layer of a
+/// [`Sequential`](crate::Sequential), see [`Module::state_dict`](crate::module::Module).
+///
+/// Saved files start with the magic `RSTORCH\0` and a `u32` version, followed by the number of
+/// arrays as a `u64`. Every array is written as its name (length as `u64` and UTF-8 bytes), the
+/// size in bytes of its elements as a `u8` (4 for `f32`, 8 for `f64`), the number of dimensions
+/// and every dimension as `u64`, and its elements in row-major order. All numbers are little
+/// endian. Arrays are converted to the float type of the state dict when read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateDict<F = f64> {
+    arrays: BTreeMap<String, ArrayD<F>>,
+}
+
+impl<F> Default for StateDict<F> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            arrays: BTreeMap::new(),
+        }
+    }
+}
+
+impl<F: Float> StateDict<F> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, name: impl Into<String>, array: ArrayD<F>) -> Option<ArrayD<F>> {
+        self.arrays.insert(name.into(), array)
+    }
+
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&ArrayD<F>> {
+        self.arrays.get(name)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, name: &str) -> Option<ArrayD<F>> {
+        self.arrays.remove(name)
+    }
+
+    #[inline]
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.arrays.contains_key(name)
+    }
+
+    /// Names in lexicographic order.
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.arrays.keys().map(String::as_str)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ArrayD<F>)> {
+        self.arrays
+            .iter()
+            .map(|(name, array)| (name.as_str(), array))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.arrays.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.arrays.is_empty()
+    }
+
+    /// Converts every array to another float type, e.g. to export a `f64` model as `f32`.
+    #[must_use]
+    pub fn cast<G: Float>(&self) -> StateDict<G> {
+        self.iter()
+            .map(|(name, array)| {
+                let array = array.mapv(|x| G::from_f64(x.to_f64().unwrap()));
+                (name.to_string(), array)
+            })
+            .collect()
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<()> {
+        let size = std::mem::size_of::<F>();
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        write_len(&mut writer, self.arrays.len())?;
+
+        for (name, array) in &self.arrays {
+            write_len(&mut writer, name.len())?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(&[size as u8])?;
+            write_len(&mut writer, array.ndim())?;
+            for &dim in array.shape() {
+                write_len(&mut writer, dim)?;
+            }
+
+            let mut bytes = Vec::with_capacity(array.len() * size);
+            for &x in array.iter() {
+                let x = x.to_f64().unwrap();
+                match size {
+                    4 => bytes.extend_from_slice(&(x as f32).to_le_bytes()),
+                    _ => bytes.extend_from_slice(&x.to_le_bytes()),
+                }
+            }
+            writer.write_all(&bytes)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self> {
+        let mut magic = [0; 8];
+        read_exact(&mut reader, &mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::Format("not a state dict file".to_string()));
+        }
+        let mut version = [0; 4];
+        read_exact(&mut reader, &mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(Error::Format(format!("unsupported version {version}")));
+        }
+
+        let mut state_dict = Self::new();
+        for _ in 0..read_len(&mut reader)? {
+            let len = read_len(&mut reader)?;
+            let name = read_bytes(&mut reader, len)?;
+            let name = String::from_utf8(name)
+                .map_err(|_| Error::Format("name is not valid UTF-8".to_string()))?;
+
+            let mut size = [0; 1];
+            read_exact(&mut reader, &mut size)?;
+            let size = size[0] as usize;
+            if size != 4 && size != 8 {
+                return Err(Error::Format(format!(
+                    "{name} has elements of {size} bytes"
+                )));
+            }
+
+            let shape = (0..read_len(&mut reader)?)
+                .map(|_| read_len(&mut reader))
+                .collect::<Result<Vec<_>>>()?;
+            let len = shape
+                .iter()
+                .try_fold(size, |len, &dim| len.checked_mul(dim))
+                .ok_or_else(|| Error::Format(format!("{name} is too large")))?;
+
+            let data = read_bytes(&mut reader, len)?
+                .chunks_exact(size)
+                .map(|bytes| match size {
+                    4 => F::from_f64(f32::from_le_bytes(bytes.try_into().unwrap()) as f64),
+                    _ => F::from_f64(f64::from_le_bytes(bytes.try_into().unwrap())),
+                })
+                .collect();
+            let array = ArrayD::from_shape_vec(shape, data).unwrap();
+            if state_dict.insert(name.clone(), array).is_some() {
+                return Err(Error::Format(format!("{name} appears twice")));
+            }
+        }
+        Ok(state_dict)
+    }
+
+    #[inline]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    #[inline]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+#[inline]
+pub(crate) fn write_len<W: Write>(writer: &mut W, len: usize) -> Result<()> {
+    writer.write_all(&(len as u64).to_le_bytes())?;
+    Ok(())
+}
+
+/// Reports a truncated file as a format error.
+#[inline]
+pub(crate) fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<()> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => Error::Format("unexpected end of file".to_string()),
+        _ => Error::Io(e),
+    })
+}
+
+#[inline]
+pub(crate) fn read_len<R: Read>(reader: &mut R) -> Result<usize> {
+    let mut len = [0; 8];
+    read_exact(reader, &mut len)?;
+    usize::try_from(u64::from_le_bytes(len))
+        .map_err(|_| Error::Format("length does not fit in memory".to_string()))
+}
+
+/// Reads `len` bytes without trusting `len` for the allocation, as the file may be corrupted.
+#[inline]
+pub(crate) fn read_bytes<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    match bytes.len() == len {
+        true => Ok(bytes),
+        false => Err(Error::Format("unexpected end of file".to_string())),
+    }
+}
+
+impl<F> FromIterator<(String, ArrayD<F>)> for StateDict<F> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (String, ArrayD<F>)>>(iter: I) -> Self {
+        Self {
+            arrays: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<F> IntoIterator for StateDict<F> {
+    type Item = (String, ArrayD<F>);
+    type IntoIter = btree_map::IntoIter<String, ArrayD<F>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.arrays.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::Module;
+    use crate::{assert_array_eq, sequential, Linear, ReLU, Sequential};
+
+    fn state_dict() -> StateDict {
+        let mut state_dict = StateDict::new();
+        state_dict.insert("0.weight", array![[1.0, -2.5], [3.0, 1e-10]].into_dyn());
+        state_dict.insert("0.bias", array![0.5, 0.25].into_dyn());
+        state_dict.insert("scalar", arr0(7.0).into_dyn());
+        state_dict
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bytes = Vec::new();
+        state_dict().write_to(&mut bytes).unwrap();
+        assert_eq!(
+            state_dict(),
+            StateDict::read_from(bytes.as_slice()).unwrap()
+        );
+
+        // Converted to the requested float type
+        let result = StateDict::<f32>::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(
+            vec!["0.bias", "0.weight", "scalar"],
+            result.keys().collect::<Vec<_>>()
+        );
+        assert_array_eq!(
+            result.get("0.bias").unwrap(),
+            array![0.5f32, 0.25].into_dyn()
+        );
+    }
+
+    #[test]
+    fn invalid_files() {
+        let mut bytes = Vec::new();
+        state_dict().write_to(&mut bytes).unwrap();
+
+        let truncated = StateDict::<f64>::read_from(&bytes[..bytes.len() - 1]);
+        assert!(matches!(truncated, Err(Error::Format(_))));
+
+        bytes[0] = b'X';
+        let magic = StateDict::<f64>::read_from(bytes.as_slice());
+        assert!(matches!(magic, Err(Error::Format(_))));
+    }
+
+    #[test]
+    fn save_and_load_module() {
+        let path = std::env::temp_dir().join(format!("rstorch-{}.state", std::process::id()));
+        let data = array![[1.0, 2.0, 3.0], [-4.0, -5.0, -6.0]].into_dyn();
+
+        let mut module: Sequential = sequential!(Linear(3, 10), ReLU(), Linear(10, 2));
+        let expected = module.forward(data.clone());
+        module.state_dict().save(&path).unwrap();
+
+        let mut loaded: Sequential = sequential!(Linear(3, 10), ReLU(), Linear(10, 2));
+        loaded
+            .load_state_dict(&StateDict::load(&path).unwrap())
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let result = loaded.forward(data);
+        assert_array_eq!(result, expected);
+    }
+
+    #[test]
+    fn mismatched_module() {
+        let mut module: Sequential = sequential!(Linear(3, 10), ReLU(), Linear(10, 2));
+        let mut state_dict = module.state_dict();
+        assert_eq!(
+            vec!["0.bias", "0.weight", "2.bias", "2.weight"],
+            state_dict.keys().collect::<Vec<_>>()
+        );
+
+        let weight = state_dict.remove("2.weight").unwrap();
+        state_dict.insert("3.weight", weight.clone());
+        match module.load_state_dict(&state_dict) {
+            Err(Error::StateDictKeys {
+                missing,
+                unexpected,
+            }) => {
+                assert_eq!(vec!["2.weight"], missing);
+                assert_eq!(vec!["3.weight"], unexpected);
+            }
+            result => panic!("{result:?}"),
+        }
+
+        state_dict.remove("3.weight");
+        state_dict.insert("2.weight", weight.t().to_owned());
+        let before = module.state_dict();
+        match module.load_state_dict(&state_dict) {
+            Err(Error::ShapeMismatch {
+                name,
+                expected,
+                found,
+            }) => {
+                assert_eq!("2.weight", name);
+                assert_eq!(vec![2, 10], expected);
+                assert_eq!(vec![10, 2], found);
+            }
+            result => panic!("{result:?}"),
+        }
+        // Nothing is loaded on failure
+        assert_eq!(before, module.state_dict());
+    }
+}
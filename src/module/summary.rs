@@ -0,0 +1,184 @@
+use super::LayerShape;
+use crate::Float;
+use std::fmt;
+
+/// Layers of a [`Sequential`](super::Sequential) with their output shape and number of
+/// parameters, and the estimated memory of a training step, see
+/// [`Sequential::summary`](super::Sequential::summary). Printed as a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub input_shape: Vec<usize>,
+    pub layers: Vec<LayerShape>,
+    /// Bytes of an element of the arrays
+    element_size: usize,
+}
+
+impl Summary {
+    #[inline]
+    #[must_use]
+    pub(crate) fn new<F: Float>(input_shape: &[usize], layers: Vec<LayerShape>) -> Self {
+        Self {
+            input_shape: input_shape.to_vec(),
+            layers,
+            element_size: std::mem::size_of::<F>(),
+        }
+    }
+
+    #[inline]
+    pub fn num_parameters(&self) -> usize {
+        self.layers.iter().map(|l| l.num_parameters).sum()
+    }
+
+    #[inline]
+    pub fn num_trainable(&self) -> usize {
+        self.layers.iter().map(|l| l.num_trainable).sum()
+    }
+
+    #[inline]
+    pub fn input_bytes(&self) -> usize {
+        self.input_shape.iter().product::<usize>() * self.element_size
+    }
+
+    /// Outputs of every layer, kept by the forward pass, and their gradients.
+    #[inline]
+    pub fn activation_bytes(&self) -> usize {
+        let len: usize = self
+            .layers
+            .iter()
+            .map(|l| l.output_shape.iter().product::<usize>())
+            .sum();
+        2 * len * self.element_size
+    }
+
+    #[inline]
+    pub fn parameter_bytes(&self) -> usize {
+        self.num_parameters() * self.element_size
+    }
+}
+
+/// Number with thousands separators, like `39,760`.
+fn separated(n: usize) -> String {
+    let digits = n.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    result
+}
+
+#[inline]
+fn megabytes(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = ["Layer", "Type", "Output Shape", "Param #", "Trainable #"];
+        let rows: Vec<[String; 5]> = self
+            .layers
+            .iter()
+            .map(|l| {
+                [
+                    l.name.clone(),
+                    l.type_name.to_string(),
+                    format!("{:?}", l.output_shape),
+                    separated(l.num_parameters),
+                    separated(l.num_trainable),
+                ]
+            })
+            .collect();
+
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let width = widths.iter().sum::<usize>() + 4 * (widths.len() - 1);
+        let line = |f: &mut fmt::Formatter<'_>, c: &str| writeln!(f, "{}", c.repeat(width));
+        let row = |f: &mut fmt::Formatter<'_>, cells: [&str; 5]| {
+            let [w0, w1, w2, w3, w4] = widths;
+            writeln!(
+                f,
+                "{:<w0$}    {:<w1$}    {:<w2$}    {:>w3$}    {:>w4$}",
+                cells[0], cells[1], cells[2], cells[3], cells[4]
+            )
+        };
+
+        line(f, "-")?;
+        row(f, header)?;
+        line(f, "=")?;
+        for cells in &rows {
+            row(f, cells.each_ref().map(String::as_str))?;
+        }
+        line(f, "=")?;
+
+        let (total, trainable) = (self.num_parameters(), self.num_trainable());
+        writeln!(f, "Total params: {}", separated(total))?;
+        writeln!(f, "Trainable params: {}", separated(trainable))?;
+        writeln!(f, "Non-trainable params: {}", separated(total - trainable))?;
+        line(f, "-")?;
+
+        let sizes = [
+            ("Input size (MB)", self.input_bytes()),
+            ("Forward/backward pass size (MB)", self.activation_bytes()),
+            ("Params size (MB)", self.parameter_bytes()),
+            (
+                "Estimated Total Size (MB)",
+                self.input_bytes() + self.activation_bytes() + self.parameter_bytes(),
+            ),
+        ];
+        for (name, bytes) in sizes {
+            writeln!(f, "{name}: {:.2}", megabytes(bytes))?;
+        }
+        line(f, "-")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::{Flatten, Linear, Module, ReLU, Sequential};
+    use crate::sequential;
+
+    #[test]
+    fn summary() {
+        let mut module: Sequential<f32> =
+            sequential!(Flatten(), Linear(784, 50), ReLU(), Linear(50, 10));
+        module.children()[1].1.freeze();
+        let summary = module.summary(&[32, 1, 28, 28]).unwrap();
+
+        assert_eq!(784 * 50 + 50 + 510, summary.num_parameters());
+        assert_eq!(510, summary.num_trainable());
+        assert_eq!(32 * 784 * 4, summary.input_bytes());
+        assert_eq!(
+            2 * 32 * (784 + 50 + 50 + 10) * 4,
+            summary.activation_bytes()
+        );
+
+        let table = summary.to_string();
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(
+            "Layer    Type       Output Shape    Param #    Trainable #",
+            lines[1]
+        );
+        assert_eq!(
+            "1        Linear     [32, 50]         39,250              0",
+            lines[4]
+        );
+        assert!(lines.contains(&"Total params: 39,760"));
+        assert!(lines.contains(&"Non-trainable params: 39,250"));
+        assert!(lines.contains(&"Params size (MB): 0.15"));
+    }
+
+    #[test]
+    fn separators() {
+        assert_eq!("0", separated(0));
+        assert_eq!("999", separated(999));
+        assert_eq!("1,000", separated(1000));
+        assert_eq!("12,345,678", separated(12_345_678));
+    }
+}
@@ -0,0 +1,404 @@
+use super::group::{group_methods, Hyperparameters, ParamGroups};
+use super::{buffer, check_state, lr_state, param_key, parameter_shapes};
+use super::{GradClip, Optimizer, ParamGroup};
+use crate::checkpoint;
+use crate::module::Parameter;
+use crate::prelude::Module;
+use crate::{Error, Float, Result};
+use ndarray::prelude::*;
+use ndarray::Zip;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug)]
+struct State<F> {
+    step: i32,
+    /// Running average of the gradient
+    m: ArrayD<F>,
+    /// Running average of the squared gradient
+    v: ArrayD<F>,
+}
+
+/// Adam, from [Adam: A Method for Stochastic Optimization](https://arxiv.org/abs/1412.6980).
+///
+/// The weight decay is added to the gradient (L2 regularization), see [`AdamW`] for the
+/// decoupled variant. The momentum of a [`ParamGroup`] sets its first beta.
+#[derive(Debug)]
+pub struct Adam<F = f64> {
+    /// With the first beta as momentum
+    groups: ParamGroups<F>,
+    beta2: F,
+    eps: F,
+    decoupled_weight_decay: bool,
+    clip: Option<GradClip<F>>,
+    state: HashMap<String, State<F>>,
+}
+
+impl<F: Float> Adam<F> {
+    /// Defaults to `betas = (0.9, 0.999)`, `eps = 1e-8` and no weight decay.
+    #[inline]
+    #[must_use]
+    pub fn new(lr: F) -> Self {
+        Self {
+            groups: ParamGroups::new(lr, F::from_f64(0.9), F::zero()),
+            beta2: F::from_f64(0.999),
+            eps: F::from_f64(1e-8),
+            decoupled_weight_decay: false,
+            clip: None,
+            state: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn betas(mut self, beta1: F, beta2: F) -> Self {
+        self.groups.default.momentum = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn eps(mut self, eps: F) -> Self {
+        self.eps = eps;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn weight_decay(mut self, weight_decay: F) -> Self {
+        self.groups.default.weight_decay = weight_decay;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn param_group(mut self, group: ParamGroup<F>) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// Clips the gradients before every step.
+    #[inline]
+    #[must_use]
+    pub fn clip_grad(mut self, clip: GradClip<F>) -> Self {
+        self.clip = Some(clip);
+        self
+    }
+
+    fn update(
+        &mut self,
+        Parameter {
+            name, parm, grad, ..
+        }: Parameter<'_, F>,
+    ) {
+        let Hyperparameters {
+            lr,
+            momentum: beta1,
+            weight_decay,
+        } = self.groups.get(&name);
+        let beta2 = self.beta2;
+        let state = self.state.entry(name).or_insert_with(|| State {
+            step: 0,
+            m: ArrayD::zeros(parm.raw_dim()),
+            v: ArrayD::zeros(parm.raw_dim()),
+        });
+        state.step += 1;
+
+        let mut grad = grad.view();
+        let decayed;
+        if weight_decay != F::zero() {
+            if self.decoupled_weight_decay {
+                *parm *= F::one() - lr * weight_decay;
+            } else {
+                decayed = &grad + &(&*parm * weight_decay);
+                grad = decayed.view();
+            }
+        }
+
+        Zip::from(&mut state.m)
+            .and(&mut state.v)
+            .and(&grad)
+            .for_each(|m, v, &g| {
+                *m = beta1 * *m + (F::one() - beta1) * g;
+                *v = beta2 * *v + (F::one() - beta2) * g * g;
+            });
+
+        let correction1 = F::one() - beta1.powi(state.step);
+        let correction2 = F::one() - beta2.powi(state.step);
+        let step_size = lr / correction1;
+        Zip::from(&mut *parm)
+            .and(&state.m)
+            .and(&state.v)
+            .for_each(|p, &m, &v| {
+                *p -= step_size * m / ((v / correction2).sqrt() + self.eps);
+            });
+    }
+}
+
+impl<F: Float> Optimizer<F> for Adam<F> {
+    fn step<M: Module<F>>(&mut self, module: &mut M) {
+        if let Some(clip) = &self.clip {
+            clip.apply(module);
+        }
+        module
+            .parameters()
+            .trainable()
+            .for_each(|parameter| self.update(parameter));
+    }
+
+    group_methods!();
+
+    fn state(&self) -> checkpoint::State<F> {
+        let mut state = lr_state(self);
+        for (name, State { step, m, v }) in &self.state {
+            let key = |buffer| format!("state.{name}.{buffer}");
+            state.counters.insert(key("step"), *step as u64);
+            state.arrays.insert(key("exp_avg"), m.clone());
+            state.arrays.insert(key("exp_avg_sq"), v.clone());
+        }
+        state
+    }
+
+    fn load_state<M: Module<F>>(
+        &mut self,
+        module: &mut M,
+        state: &checkpoint::State<F>,
+    ) -> Result<()> {
+        let lrs = check_state(self, state, &["step", "exp_avg", "exp_avg_sq"])?;
+        let shapes = parameter_shapes(module);
+        let names: BTreeSet<_> = state
+            .arrays
+            .keys()
+            .chain(state.counters.keys().map(String::as_str))
+            .filter_map(|key| Some(param_key(key)?.0))
+            .collect();
+        let parameters = names
+            .into_iter()
+            .map(|name| {
+                let key = format!("state.{name}.step");
+                let step = i32::try_from(state.counter(&key)?)
+                    .map_err(|_| Error::Format(format!("{key} does not fit in an i32")))?;
+                let parameter = State {
+                    step,
+                    m: buffer(state, &shapes, name, "exp_avg")?.clone(),
+                    v: buffer(state, &shapes, name, "exp_avg_sq")?.clone(),
+                };
+                Ok((name.to_string(), parameter))
+            })
+            .collect::<Result<_>>()?;
+
+        self.state = parameters;
+        for (group, lr) in lrs.into_iter().enumerate() {
+            self.groups.set_lr(group, lr);
+        }
+        Ok(())
+    }
+}
+
+/// Adam with decoupled weight decay, from
+/// [Decoupled Weight Decay Regularization](https://arxiv.org/abs/1711.05101): the parameters are
+/// shrunk by `lr * weight_decay` directly instead of through the gradient.
+#[derive(Debug)]
+pub struct AdamW<F = f64> {
+    adam: Adam<F>,
+}
+
+impl<F: Float> AdamW<F> {
+    /// Defaults to `betas = (0.9, 0.999)`, `eps = 1e-8` and `weight_decay = 1e-2`.
+    #[inline]
+    #[must_use]
+    pub fn new(lr: F) -> Self {
+        let mut adam = Adam::new(lr).weight_decay(F::from_f64(1e-2));
+        adam.decoupled_weight_decay = true;
+        Self { adam }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn betas(self, beta1: F, beta2: F) -> Self {
+        Self {
+            adam: self.adam.betas(beta1, beta2),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn eps(self, eps: F) -> Self {
+        Self {
+            adam: self.adam.eps(eps),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn weight_decay(self, weight_decay: F) -> Self {
+        Self {
+            adam: self.adam.weight_decay(weight_decay),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn param_group(self, group: ParamGroup<F>) -> Self {
+        Self {
+            adam: self.adam.param_group(group),
+        }
+    }
+
+    /// Clips the gradients before every step.
+    #[inline]
+    #[must_use]
+    pub fn clip_grad(self, clip: GradClip<F>) -> Self {
+        Self {
+            adam: self.adam.clip_grad(clip),
+        }
+    }
+}
+
+impl<F: Float> Optimizer<F> for AdamW<F> {
+    #[inline]
+    fn step<M: Module<F>>(&mut self, module: &mut M) {
+        self.adam.step(module)
+    }
+
+    #[inline]
+    fn num_groups(&self) -> usize {
+        self.adam.num_groups()
+    }
+
+    #[inline]
+    fn group_lr(&self, group: usize) -> F {
+        self.adam.group_lr(group)
+    }
+
+    #[inline]
+    fn set_group_lr(&mut self, group: usize, lr: F) {
+        self.adam.set_group_lr(group, lr)
+    }
+
+    #[inline]
+    fn state(&self) -> checkpoint::State<F> {
+        self.adam.state()
+    }
+
+    #[inline]
+    fn load_state<M: Module<F>>(
+        &mut self,
+        module: &mut M,
+        state: &checkpoint::State<F>,
+    ) -> Result<()> {
+        self.adam.load_state(module, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+    use crate::optim::tests::{resumes, Constant};
+    use crate::{sequential, Linear, ReLU, Sequential};
+
+    #[test]
+    fn first_steps_follow_gradient_sign() {
+        // With a constant gradient, m / sqrt(v) is the sign of the gradient once bias-corrected
+        let mut optim = Adam::new(0.1);
+        let mut module = Constant::new(
+            array![1.0, 2.0, 3.0].into_dyn(),
+            array![0.5, -2.0, 0.0].into_dyn(),
+        );
+
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.9, 2.1, 3.0].into_dyn());
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.8, 2.2, 3.0].into_dyn());
+    }
+
+    #[test]
+    fn weight_decay() {
+        let mut module = Constant::new(array![1.0, -2.0].into_dyn(), array![0.0, 0.0].into_dyn());
+        let mut optim = Adam::new(0.1).weight_decay(0.5);
+        optim.step(&mut module);
+        // The decay is part of the gradient, so the step is still lr * sign
+        assert_array_eq!(module.parm, array![0.9, -1.9].into_dyn());
+
+        let mut module = Constant::new(array![1.0, -2.0].into_dyn(), array![0.0, 0.0].into_dyn());
+        let mut optim = AdamW::new(0.1).weight_decay(0.5);
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.95, -1.9].into_dyn());
+    }
+
+    #[test]
+    fn state_per_parameter() {
+        let mut optim = Adam::new(0.01);
+        let mut module: Sequential = sequential!(Linear(2, 3), ReLU(), Linear(3, 1));
+        for _ in 0..3 {
+            optim.zero_grad(&mut module);
+            module.forward(ArrayD::ones(vec![4, 2]));
+            module.backward(ArrayD::ones(vec![4, 1])).unwrap();
+            optim.step(&mut module);
+        }
+
+        let mut names: Vec<_> = optim.state.keys().cloned().collect();
+        names.sort();
+        assert_eq!(vec!["0.bias", "0.weight", "2.bias", "2.weight"], names);
+        assert!(optim.state.values().all(|s| s.step == 3));
+        assert_eq!(&[3, 2], optim.state["0.weight"].m.shape());
+    }
+
+    #[test]
+    fn fits_linear_regression() {
+        // y = 2x - 1
+        let x = array![[-1.0], [0.0], [1.0], [2.0]].into_dyn();
+        let y = array![[-3.0], [-1.0], [1.0], [3.0]].into_dyn();
+        let mut module = Linear::new(1, 1);
+        let mut optim = AdamW::new(0.1f64).weight_decay(0.0);
+
+        for _ in 0..500 {
+            optim.zero_grad(&mut module);
+            let pred = module.forward(x.clone());
+            module.backward(pred - &y).unwrap();
+            optim.step(&mut module);
+        }
+        let pred = module.forward(x);
+        assert_array_eq!(pred, y, 1e-3);
+    }
+
+    #[test]
+    fn group_momentum_is_beta1() {
+        let mut optim = Adam::new(0.1).param_group(
+            ParamGroup::new("slow", |name| name == "parm")
+                .lr(0.01)
+                .momentum(0.5),
+        );
+        let mut module = Constant::new(array![1.0].into_dyn(), array![1.0].into_dyn());
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.99].into_dyn());
+
+        // m = 0.5 * 0.5 + 0.5 * -2
+        module.grad = array![-2.0].into_dyn();
+        optim.step(&mut module);
+        let state = &optim.state["parm"];
+        assert_array_eq!(state.m, array![-0.75].into_dyn());
+        assert_eq!(2, state.step);
+    }
+
+    #[test]
+    fn resume() {
+        resumes(|| Adam::new(0.1));
+        resumes(|| AdamW::new(0.1));
+    }
+
+    #[test]
+    fn resume_step_overflow() {
+        let mut module = Constant::new(array![1.0].into_dyn(), array![0.5].into_dyn());
+        let mut optim = Adam::new(0.1);
+        optim.step(&mut module);
+        let mut state = optim.state();
+        state
+            .counters
+            .insert("state.parm.step".to_string(), u64::MAX);
+
+        let result = Adam::new(0.1).load_state(&mut module, &state);
+        assert!(matches!(result, Err(Error::Format(_))));
+    }
+}
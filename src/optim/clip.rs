@@ -0,0 +1,174 @@
+use crate::module::Parameter;
+use crate::prelude::Module;
+use crate::Float;
+
+/// Global `norm_type`-norm of the gradients of a module, as if they were concatenated in a
+/// single vector. `F::infinity()` gives the maximum absolute value.
+fn grad_norm<F: Float>(parameters: &[Parameter<'_, F>], norm_type: F) -> F {
+    let grads = parameters.iter().flat_map(|p| p.grad.iter());
+    if norm_type == F::infinity() {
+        grads.fold(F::zero(), |max, g| max.max(g.abs()))
+    } else {
+        grads
+            .map(|g| g.abs().powf(norm_type))
+            .sum::<F>()
+            .powf(F::one() / norm_type)
+    }
+}
+
+/// Scales the gradients of the trainable parameters of the module down so that their global
+/// `norm_type`-norm is at most `max_norm`, and returns their norm before clipping.
+/// `F::infinity()` clips by the maximum absolute value.
+///
+/// ```
+/// use rstorch::optim::clip_grad_norm;
+/// use rstorch::prelude::*;
+/// use rstorch::Linear;
+///
+/// let mut linear = Linear::new(3, 1);
+/// linear.forward(ArrayD::ones(vec![1, 3]));
+/// linear.backward(ArrayD::from_elem(vec![1, 1], 100.0)).unwrap();
+///
+/// let norm = clip_grad_norm(&mut linear, 1.0, 2.0);
+/// assert!(norm > 1.0);
+/// ```
+pub fn clip_grad_norm<F: Float, M: Module<F>>(module: &mut M, max_norm: F, norm_type: F) -> F {
+    assert!(norm_type > F::zero(), "Norm type must be positive");
+    let parameters: Vec<_> = module.parameters().trainable().collect();
+    let norm = grad_norm(&parameters, norm_type);
+
+    let coefficient = max_norm / (norm + F::from_f64(1e-6));
+    if coefficient < F::one() {
+        for Parameter { grad, .. } in parameters {
+            *grad *= coefficient;
+        }
+    }
+    norm
+}
+
+/// Clamps the gradients of the trainable parameters of the module to
+/// `[-clip_value, clip_value]`.
+pub fn clip_grad_value<F: Float, M: Module<F>>(module: &mut M, clip_value: F) {
+    assert!(clip_value >= F::zero(), "Clip value must not be negative");
+    for Parameter { grad, .. } in module.parameters().trainable() {
+        grad.mapv_inplace(|g| g.max(-clip_value).min(clip_value));
+    }
+}
+
+/// Gradient clipping applied by an optimizer before every step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradClip<F = f64> {
+    /// See [`clip_grad_norm`]
+    Norm { max_norm: F, norm_type: F },
+    /// See [`clip_grad_value`]
+    Value(F),
+}
+
+impl<F: Float> GradClip<F> {
+    /// Clips the L2 norm of the gradients.
+    #[inline]
+    #[must_use]
+    pub fn norm(max_norm: F) -> Self {
+        Self::Norm {
+            max_norm,
+            norm_type: F::from_f64(2.0),
+        }
+    }
+
+    #[inline]
+    pub fn apply<M: Module<F>>(&self, module: &mut M) {
+        match *self {
+            Self::Norm {
+                max_norm,
+                norm_type,
+            } => {
+                clip_grad_norm(module, max_norm, norm_type);
+            }
+            Self::Value(clip_value) => clip_grad_value(module, clip_value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+    use crate::optim::tests::Constant;
+    use crate::optim::{Adam, Optimizer};
+    use crate::{Sequential, SGD};
+    use ndarray::prelude::*;
+
+    fn two_layers() -> Sequential {
+        Sequential::new(vec![
+            Box::new(Constant::new(
+                array![0.0].into_dyn(),
+                array![3.0].into_dyn(),
+            )),
+            Box::new(Constant::new(
+                array![0.0, 0.0].into_dyn(),
+                array![-4.0, 0.0].into_dyn(),
+            )),
+        ])
+    }
+
+    fn grads(module: &mut Sequential) -> Vec<f64> {
+        module
+            .parameters()
+            .iter()
+            .flat_map(|p| p.grad.iter().copied().collect::<Vec<_>>())
+            .collect()
+    }
+
+    #[test]
+    fn norm() {
+        let mut module = two_layers();
+        assert_eq!(5.0, clip_grad_norm(&mut module, 1.0, 2.0));
+        let expected = array![0.6, -0.8, 0.0];
+        assert_array_eq!(Array::from(grads(&mut module)), expected);
+
+        // Already below the maximum
+        assert!((clip_grad_norm(&mut module, 2.0, 2.0) - 1.0).abs() < 1e-6);
+        assert_array_eq!(Array::from(grads(&mut module)), expected, 1e-5);
+    }
+
+    #[test]
+    fn other_norms() {
+        let mut module = two_layers();
+        assert_eq!(7.0, clip_grad_norm(&mut module, 3.5, 1.0));
+        assert_array_eq!(
+            Array::from(grads(&mut module)),
+            array![1.5, -2.0, 0.0],
+            1e-5
+        );
+
+        let mut module = two_layers();
+        assert_eq!(4.0, clip_grad_norm(&mut module, 2.0, f64::INFINITY));
+        assert_array_eq!(
+            Array::from(grads(&mut module)),
+            array![1.5, -2.0, 0.0],
+            1e-5
+        );
+    }
+
+    #[test]
+    fn value() {
+        let mut module = two_layers();
+        clip_grad_value(&mut module, 3.5);
+        assert_array_eq!(Array::from(grads(&mut module)), array![3.0, -3.5, 0.0]);
+    }
+
+    #[test]
+    fn optimizer_option() {
+        let mut module = two_layers();
+        let mut optim = SGD::new(1.0).clip_grad(GradClip::norm(1.0));
+        optim.step(&mut module);
+        let parms: Vec<_> = module.parameters().iter().map(|p| p.parm.sum()).collect();
+        assert!((parms[0] + 0.6).abs() < 1e-5 && (parms[1] - 0.8).abs() < 1e-5);
+
+        // Adam is invariant to the scale of the gradient, but not to its clamping
+        let mut module = two_layers();
+        let mut optim = Adam::new(1.0).clip_grad(GradClip::Value(1.0));
+        optim.step(&mut module);
+        assert_array_eq!(Array::from(grads(&mut module)), array![1.0, -1.0, 0.0]);
+    }
+}
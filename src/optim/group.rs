@@ -0,0 +1,217 @@
+use crate::Float;
+use std::fmt;
+
+/// Subset of the parameters of a module, selected by [name](crate::module::Parameter), optimized
+/// with its own hyperparameters. The ones left unset fall back to those of the optimizer.
+///
+/// A parameter belongs to the first group that selects it, or to the default group of the
+/// optimizer if none does.
+///
+/// ```
+/// use rstorch::optim::ParamGroup;
+/// use rstorch::SGD;
+///
+/// let optim = SGD::new(0.1)
+///     .weight_decay(1e-4)
+///     .param_group(ParamGroup::new("no_decay", |name| name.ends_with("bias")).weight_decay(0.0))
+///     .param_group(ParamGroup::new("backbone", |name| name.starts_with("0.")).lr(0.01));
+/// ```
+pub struct ParamGroup<F = f64> {
+    name: String,
+    filter: Box<dyn Fn(&str) -> bool>,
+    lr: Option<F>,
+    momentum: Option<F>,
+    weight_decay: Option<F>,
+}
+
+impl<F: Float> ParamGroup<F> {
+    #[inline]
+    #[must_use]
+    pub fn new(name: &str, filter: impl Fn(&str) -> bool + 'static) -> Self {
+        Self {
+            name: name.to_string(),
+            filter: Box::new(filter),
+            lr: None,
+            momentum: None,
+            weight_decay: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn lr(mut self, lr: F) -> Self {
+        self.lr = Some(lr);
+        self
+    }
+
+    /// Momentum of the optimizer, e.g. the first beta of [`Adam`](crate::optim::Adam).
+    #[inline]
+    #[must_use]
+    pub fn momentum(mut self, momentum: F) -> Self {
+        self.momentum = Some(momentum);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn weight_decay(mut self, weight_decay: F) -> Self {
+        self.weight_decay = Some(weight_decay);
+        self
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn contains(&self, parameter: &str) -> bool {
+        (self.filter)(parameter)
+    }
+}
+
+impl<F: fmt::Debug> fmt::Debug for ParamGroup<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParamGroup")
+            .field("name", &self.name)
+            .field("lr", &self.lr)
+            .field("momentum", &self.momentum)
+            .field("weight_decay", &self.weight_decay)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Hyperparameters shared by the optimizers, which can be set per group.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Hyperparameters<F> {
+    pub lr: F,
+    pub momentum: F,
+    pub weight_decay: F,
+}
+
+/// Default hyperparameters of an optimizer, at index 0, followed by its parameter groups.
+#[derive(Debug)]
+pub(crate) struct ParamGroups<F> {
+    pub default: Hyperparameters<F>,
+    groups: Vec<ParamGroup<F>>,
+}
+
+impl<F: Float> ParamGroups<F> {
+    #[inline]
+    pub fn new(lr: F, momentum: F, weight_decay: F) -> Self {
+        Self {
+            default: Hyperparameters {
+                lr,
+                momentum,
+                weight_decay,
+            },
+            groups: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, group: ParamGroup<F>) {
+        self.groups.push(group);
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.groups.len() + 1
+    }
+
+    #[inline]
+    pub fn lr(&self, index: usize) -> F {
+        match index {
+            0 => self.default.lr,
+            _ => self.groups[index - 1].lr.unwrap_or(self.default.lr),
+        }
+    }
+
+    #[inline]
+    pub fn set_lr(&mut self, index: usize, lr: F) {
+        match index {
+            0 => self.default.lr = lr,
+            _ => self.groups[index - 1].lr = Some(lr),
+        }
+    }
+
+    /// Momentum of every group, starting with the default one.
+    #[inline]
+    pub fn momentums(&self) -> impl Iterator<Item = F> + '_ {
+        let groups = self.groups.iter();
+        let momentums = groups.map(|group| group.momentum.unwrap_or(self.default.momentum));
+        std::iter::once(self.default.momentum).chain(momentums)
+    }
+
+    /// Hyperparameters of the group of the given parameter.
+    #[inline]
+    pub fn get(&self, parameter: &str) -> Hyperparameters<F> {
+        let default = self.default;
+        match self.groups.iter().find(|group| group.contains(parameter)) {
+            Some(group) => Hyperparameters {
+                lr: group.lr.unwrap_or(default.lr),
+                momentum: group.momentum.unwrap_or(default.momentum),
+                weight_decay: group.weight_decay.unwrap_or(default.weight_decay),
+            },
+            None => default,
+        }
+    }
+}
+
+/// Implements the parameter group methods of [`Optimizer`](crate::optim::Optimizer) for an
+/// optimizer with a `groups` field.
+macro_rules! group_methods {
+    () => {
+        #[inline]
+        fn num_groups(&self) -> usize {
+            self.groups.len()
+        }
+
+        #[inline]
+        fn group_lr(&self, group: usize) -> F {
+            self.groups.lr(group)
+        }
+
+        #[inline]
+        fn set_group_lr(&mut self, group: usize, lr: F) {
+            self.groups.set_lr(group, lr)
+        }
+    };
+}
+pub(crate) use group_methods;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_matching_group() {
+        let mut groups = ParamGroups::new(0.1, 0.9, 1e-4);
+        groups.push(ParamGroup::new("no_decay", |n| n.ends_with("bias")).weight_decay(0.0));
+        groups.push(ParamGroup::new("backbone", |n| n.starts_with("0.")).lr(0.01));
+
+        let hyper = groups.get("0.weight");
+        assert_eq!(
+            (0.01, 0.9, 1e-4),
+            (hyper.lr, hyper.momentum, hyper.weight_decay)
+        );
+        let hyper = groups.get("0.bias");
+        assert_eq!(
+            (0.1, 0.9, 0.0),
+            (hyper.lr, hyper.momentum, hyper.weight_decay)
+        );
+        let hyper = groups.get("1.weight");
+        assert_eq!(
+            (0.1, 0.9, 1e-4),
+            (hyper.lr, hyper.momentum, hyper.weight_decay)
+        );
+
+        assert_eq!(3, groups.len());
+        assert_eq!(
+            vec![0.1, 0.1, 0.01],
+            (0..3).map(|i| groups.lr(i)).collect::<Vec<_>>()
+        );
+        groups.set_lr(1, 0.5);
+        assert_eq!(0.5, groups.get("1.bias").lr);
+    }
+}
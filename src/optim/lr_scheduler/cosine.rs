@@ -0,0 +1,111 @@
+use super::{closed_form, group_lrs};
+use crate::optim::Optimizer;
+use crate::Float;
+
+/// `eta_min + (base_lr - eta_min) * (1 + cos(pi * t / t_max)) / 2`
+#[inline]
+fn cosine<F: Float>(base_lr: F, eta_min: F, t: usize, t_max: usize) -> F {
+    let progress = F::from_usize(t) / F::from_usize(t_max);
+    let cos = (F::from_f64(std::f64::consts::PI) * progress).cos();
+    eta_min + (base_lr - eta_min) * (F::one() + cos) / F::from_f64(2.0)
+}
+
+/// Anneals the learning rate from its initial value to `eta_min` over `t_max` steps following a
+/// cosine, then back up over the next `t_max` steps.
+#[derive(Debug, Clone)]
+pub struct CosineAnnealingLR<F = f64> {
+    base_lrs: Vec<F>,
+    t_max: usize,
+    eta_min: F,
+    steps: usize,
+}
+
+impl<F: Float> CosineAnnealingLR<F> {
+    #[inline]
+    #[must_use]
+    pub fn new<O: Optimizer<F>>(optimizer: &O, t_max: usize, eta_min: F) -> Self {
+        assert!(t_max > 0, "t_max must be positive");
+        Self {
+            base_lrs: group_lrs(optimizer),
+            t_max,
+            eta_min,
+            steps: 0,
+        }
+    }
+
+    #[inline]
+    fn lr_at(&self, base_lr: F, steps: usize) -> F {
+        cosine(base_lr, self.eta_min, steps, self.t_max)
+    }
+}
+
+closed_form!(CosineAnnealingLR);
+
+/// Cosine annealing restarted from the initial learning rate at the end of every cycle, from
+/// [SGDR: Stochastic Gradient Descent with Warm Restarts](https://arxiv.org/abs/1608.03983). The
+/// first cycle lasts `t_0` steps and every next one is `t_mult` times longer.
+#[derive(Debug, Clone)]
+pub struct CosineAnnealingWarmRestarts<F = f64> {
+    base_lrs: Vec<F>,
+    t_0: usize,
+    t_mult: usize,
+    eta_min: F,
+    steps: usize,
+}
+
+impl<F: Float> CosineAnnealingWarmRestarts<F> {
+    #[inline]
+    #[must_use]
+    pub fn new<O: Optimizer<F>>(optimizer: &O, t_0: usize, t_mult: usize, eta_min: F) -> Self {
+        assert!(t_0 > 0, "t_0 must be positive");
+        assert!(t_mult > 0, "t_mult must be positive");
+        Self {
+            base_lrs: group_lrs(optimizer),
+            t_0,
+            t_mult,
+            eta_min,
+            steps: 0,
+        }
+    }
+
+    #[inline]
+    fn lr_at(&self, base_lr: F, steps: usize) -> F {
+        // Position in the current cycle
+        let (mut t, mut length) = (steps, self.t_0);
+        while t >= length {
+            t -= length;
+            length *= self.t_mult;
+        }
+        cosine(base_lr, self.eta_min, t, length)
+    }
+}
+
+closed_form!(CosineAnnealingWarmRestarts);
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{assert_close, schedule};
+    use super::*;
+    use crate::SGD;
+
+    #[test]
+    fn cosine_annealing() {
+        let mut optim = SGD::new(1.0);
+        let scheduler = CosineAnnealingLR::new(&optim, 4, 0.2);
+        let lrs = schedule(scheduler, &mut optim, 6);
+        let half = 0.4 * 2f64.sqrt() / 2.0;
+        assert_close(&[0.6 + half, 0.6, 0.6 - half, 0.2, 0.6 - half, 0.6], &lrs);
+    }
+
+    #[test]
+    fn warm_restarts() {
+        let mut optim = SGD::new(1.0);
+        let scheduler = CosineAnnealingWarmRestarts::new(&optim, 2, 2, 0.0);
+        let lrs = schedule(scheduler, &mut optim, 7);
+        let quarter = (1.0 + std::f64::consts::FRAC_1_SQRT_2) / 2.0;
+        let three_quarters = (1.0 - std::f64::consts::FRAC_1_SQRT_2) / 2.0;
+        let eighth = (1.0 + (std::f64::consts::PI / 8.0).cos()) / 2.0;
+        // Cycles of 2, 4 and 8 steps
+        assert_close(&[0.5, 1.0, quarter, 0.5, three_quarters, 1.0, eighth], &lrs);
+    }
+}
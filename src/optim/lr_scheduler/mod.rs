@@ -0,0 +1,215 @@
+//! Learning rate schedules, stepped alongside an [`Optimizer`] whose learning rate they set.
+//!
+//! ```
+//! use rstorch::optim::lr_scheduler::StepLR;
+//! use rstorch::prelude::*;
+//! use rstorch::SGD;
+//!
+//! let mut optim = SGD::new(0.1);
+//! let mut scheduler = StepLR::new(&optim, 2, 0.5);
+//! for _epoch in 0..4 {
+//!     // ... train for an epoch with `optim.step(&mut model)`
+//!     scheduler.step(&mut optim);
+//! }
+//! assert_eq!(0.025, optim.lr());
+//! ```
+use crate::checkpoint::State;
+use crate::optim::Optimizer;
+use crate::{Float, Result};
+
+mod cosine;
+mod one_cycle;
+mod plateau;
+mod step;
+mod warmup;
+
+pub use cosine::{CosineAnnealingLR, CosineAnnealingWarmRestarts};
+pub use one_cycle::OneCycleLR;
+pub use plateau::{PlateauMode, ReduceLROnPlateau};
+pub use step::{ExponentialLR, MultiStepLR, StepLR};
+pub use warmup::LinearWarmup;
+
+/// Schedule of the learning rate of every [`ParamGroup`](crate::optim::ParamGroup) of an
+/// optimizer, each group being scheduled from its own initial learning rate.
+pub trait LrScheduler<F: Float = f64> {
+    /// Advances the schedule by one step, usually an epoch, and sets the learning rates of the
+    /// optimizer accordingly.
+    fn step<O: Optimizer<F>>(&mut self, optimizer: &mut O);
+
+    /// Learning rate of the default group at the current step.
+    fn lr(&self) -> F;
+
+    /// Progress of the schedule, to resume training from a
+    /// [`Checkpoint`](crate::checkpoint::Checkpoint). The learning rates are restored with the
+    /// state of the optimizer.
+    fn state(&self) -> State<F>;
+
+    /// Restores the [state](LrScheduler::state) of a scheduler created with the same arguments.
+    fn load_state(&mut self, state: &State<F>) -> Result<()>;
+}
+
+/// Learning rate of every group of the optimizer.
+#[inline]
+fn group_lrs<F: Float, O: Optimizer<F>>(optimizer: &O) -> Vec<F> {
+    (0..optimizer.num_groups())
+        .map(|group| optimizer.group_lr(group))
+        .collect()
+}
+
+/// Schedule given as a function of the initial learning rate and the number of steps taken.
+trait ClosedForm<F: Float> {
+    fn steps_mut(&mut self) -> &mut usize;
+
+    fn steps(&self) -> usize;
+
+    fn base_lrs(&self) -> &[F];
+
+    fn lr_at(&self, base_lr: F, steps: usize) -> F;
+
+    /// Sets the learning rate of every group for the current step.
+    #[inline]
+    fn apply<O: Optimizer<F>>(&self, optimizer: &mut O) {
+        assert_eq!(
+            self.base_lrs().len(),
+            optimizer.num_groups(),
+            "Optimizer has a different number of groups than when the scheduler was created"
+        );
+        for (group, &base_lr) in self.base_lrs().iter().enumerate() {
+            optimizer.set_group_lr(group, self.lr_at(base_lr, self.steps()));
+        }
+    }
+}
+
+impl<F: Float, S: ClosedForm<F>> LrScheduler<F> for S {
+    #[inline]
+    fn step<O: Optimizer<F>>(&mut self, optimizer: &mut O) {
+        *self.steps_mut() += 1;
+        self.apply(optimizer);
+    }
+
+    #[inline]
+    fn lr(&self) -> F {
+        self.lr_at(self.base_lrs()[0], self.steps())
+    }
+
+    #[inline]
+    fn state(&self) -> State<F> {
+        let mut state = State::new();
+        state
+            .counters
+            .insert("steps".to_string(), self.steps() as u64);
+        state
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &State<F>) -> Result<()> {
+        state.check_keys(|name| name == "steps")?;
+        *self.steps_mut() = state.counter("steps")? as usize;
+        Ok(())
+    }
+}
+
+/// Implements [`ClosedForm`] for a scheduler with `steps` and `base_lrs` fields and an `lr_at`
+/// method.
+macro_rules! closed_form {
+    ($scheduler:ident) => {
+        impl<F: Float> super::ClosedForm<F> for $scheduler<F> {
+            #[inline]
+            fn steps_mut(&mut self) -> &mut usize {
+                &mut self.steps
+            }
+
+            #[inline]
+            fn steps(&self) -> usize {
+                self.steps
+            }
+
+            #[inline]
+            fn base_lrs(&self) -> &[F] {
+                &self.base_lrs
+            }
+
+            #[inline]
+            fn lr_at(&self, base_lr: F, steps: usize) -> F {
+                $scheduler::lr_at(self, base_lr, steps)
+            }
+        }
+    };
+}
+use closed_form;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Learning rates set by the scheduler over the given number of steps
+    pub(crate) fn schedule<S: LrScheduler>(
+        mut scheduler: S,
+        optim: &mut crate::SGD,
+        steps: usize,
+    ) -> Vec<f64> {
+        (0..steps)
+            .map(|_| {
+                scheduler.step(optim);
+                assert_eq!(scheduler.lr(), optim.lr());
+                optim.lr()
+            })
+            .collect()
+    }
+
+    pub(crate) fn assert_close(expected: &[f64], actual: &[f64]) {
+        assert_eq!(expected.len(), actual.len());
+        for (e, a) in expected.iter().zip(actual) {
+            assert!(
+                (e - a).abs() < 1e-9,
+                "expected={expected:?}\nactual={actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn resume() {
+        use crate::optim::lr_scheduler::CosineAnnealingLR;
+        use crate::SGD;
+
+        let mut optim = SGD::new(1.0);
+        let mut scheduler = CosineAnnealingLR::new(&optim, 10, 0.0);
+        (0..3).for_each(|_| scheduler.step(&mut optim));
+
+        let mut restarted = SGD::new(1.0);
+        let mut resumed = CosineAnnealingLR::new(&restarted, 10, 0.0);
+        resumed.load_state(&scheduler.state()).unwrap();
+        scheduler.step(&mut optim);
+        resumed.step(&mut restarted);
+        assert_eq!(optim.lr(), restarted.lr());
+    }
+
+    #[test]
+    fn groups_are_scheduled_independently() {
+        use crate::optim::lr_scheduler::{OneCycleLR, ReduceLROnPlateau, StepLR};
+        use crate::optim::ParamGroup;
+        use crate::SGD;
+
+        let optim = || SGD::new(1.0).param_group(ParamGroup::new("slow", |_| true).lr(0.1));
+        let lrs = |optim: &SGD| group_lrs(optim);
+
+        let mut sgd = optim();
+        let mut scheduler = StepLR::new(&sgd, 1, 0.5);
+        scheduler.step(&mut sgd);
+        assert_close(&[0.5, 0.05], &lrs(&sgd));
+
+        let mut sgd = optim();
+        let _scheduler = OneCycleLR::new_with_options(&mut sgd, 2.0, 10, 0.3, 4.0, 1.0);
+        assert_close(&[0.5, 0.05], &lrs(&sgd));
+
+        let mut sgd = optim();
+        let mut scheduler = ReduceLROnPlateau::new()
+            .patience(0)
+            .factor(0.5)
+            .min_lr(0.08);
+        for _ in 0..2 {
+            scheduler.step(1.0, &mut sgd);
+        }
+        assert_close(&[0.5, 0.08], &lrs(&sgd));
+    }
+}
@@ -0,0 +1,140 @@
+use super::{closed_form, group_lrs, ClosedForm};
+use crate::optim::Optimizer;
+use crate::Float;
+
+/// 1cycle policy, from [Super-Convergence](https://arxiv.org/abs/1708.07120): the learning rate
+/// increases from `max_lr / div_factor` to `max_lr` during the first `pct_start` of the
+/// `total_steps`, then decreases to `max_lr / (div_factor * final_div_factor)`, both following a
+/// cosine. The learning rate stays at its final value after `total_steps`.
+///
+/// `max_lr` is the one of the default group, the other groups keep the ratio of their learning rate
+/// to the default one.
+#[derive(Debug, Clone)]
+pub struct OneCycleLR<F = f64> {
+    /// Maximum learning rate of every group
+    base_lrs: Vec<F>,
+    total_steps: usize,
+    pct_start: F,
+    div_factor: F,
+    final_div_factor: F,
+    steps: usize,
+}
+
+impl<F: Float> OneCycleLR<F> {
+    /// Sets the learning rates of the optimizer to the initial ones.
+    ///
+    /// * `pct_start`: fraction of the steps spent increasing the learning rate
+    /// * `div_factor`: `max_lr / initial_lr`
+    /// * `final_div_factor`: `initial_lr / final_lr`
+    #[inline]
+    #[must_use]
+    pub fn new_with_options<O: Optimizer<F>>(
+        optimizer: &mut O,
+        max_lr: F,
+        total_steps: usize,
+        pct_start: F,
+        div_factor: F,
+        final_div_factor: F,
+    ) -> Self {
+        assert!(total_steps > 1, "OneCycleLR requires at least 2 steps");
+        assert!(
+            pct_start > F::zero() && pct_start < F::one(),
+            "pct_start must be in (0, 1)"
+        );
+        let lr = optimizer.lr();
+        let base_lrs = group_lrs(optimizer)
+            .into_iter()
+            .map(|group_lr| match lr == F::zero() {
+                true => max_lr,
+                false => max_lr * group_lr / lr,
+            })
+            .collect();
+        let scheduler = Self {
+            base_lrs,
+            total_steps,
+            pct_start,
+            div_factor,
+            final_div_factor,
+            steps: 0,
+        };
+        scheduler.apply(optimizer);
+        scheduler
+    }
+
+    /// Defaults to `pct_start = 0.3`, `div_factor = 25` and `final_div_factor = 1e4`.
+    #[inline]
+    #[must_use]
+    pub fn new<O: Optimizer<F>>(optimizer: &mut O, max_lr: F, total_steps: usize) -> Self {
+        Self::new_with_options(
+            optimizer,
+            max_lr,
+            total_steps,
+            F::from_f64(0.3),
+            F::from_f64(25.0),
+            F::from_f64(1e4),
+        )
+    }
+
+    #[inline]
+    fn lr_at(&self, max_lr: F, steps: usize) -> F {
+        let initial = max_lr / self.div_factor;
+        let last = F::from_usize(self.total_steps - 1);
+        let peak = (self.pct_start * F::from_usize(self.total_steps) - F::one())
+            .max(F::zero())
+            .min(last);
+        let t = F::from_usize(steps).min(last);
+
+        // Cosine from `start` to `end` as `pct` goes from 0 to 1
+        let anneal = |start: F, end: F, pct: F| {
+            let cos = (F::from_f64(std::f64::consts::PI) * pct).cos() + F::one();
+            end + (start - end) / F::from_f64(2.0) * cos
+        };
+        if t <= peak && peak > F::zero() {
+            anneal(initial, max_lr, t / peak)
+        } else {
+            let min = initial / self.final_div_factor;
+            anneal(max_lr, min, (t - peak) / (last - peak))
+        }
+    }
+}
+
+closed_form!(OneCycleLR);
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{assert_close, schedule};
+    use super::*;
+    use crate::SGD;
+
+    #[test]
+    fn defaults() {
+        let mut optim = SGD::new(123.0);
+        let _scheduler = OneCycleLR::new(&mut optim, 1.0, 10);
+        assert_close(&[0.04], &[optim.lr()]);
+    }
+
+    #[test]
+    fn one_cycle() {
+        let mut optim = SGD::new(123.0);
+        let scheduler = OneCycleLR::new_with_options(&mut optim, 1.0, 6, 0.5, 10.0, 100.0);
+        assert_close(&[0.1], &[optim.lr()]);
+
+        // Increases over steps [0, 2], decreases over [2, 5]
+        let lrs = schedule(scheduler, &mut optim, 6);
+        let last = 0.001;
+        let third = |a: f64, b: f64, c: f64| {
+            b + (a - b) / 2.0 * (c * std::f64::consts::PI).cos() + (a - b) / 2.0
+        };
+        assert_close(
+            &[
+                0.55,
+                1.0,
+                third(1.0, last, 1.0 / 3.0),
+                third(1.0, last, 2.0 / 3.0),
+                last,
+                last,
+            ],
+            &lrs,
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::checkpoint::State;
+use crate::optim::Optimizer;
+use crate::{Float, Result};
+use ndarray::arr0;
+
+/// Whether the monitored metric should decrease, like a loss, or increase, like an accuracy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlateauMode {
+    #[default]
+    Min,
+    Max,
+}
+
+/// Multiplies the learning rate by `factor` when a metric has not improved for `patience` steps.
+///
+/// Unlike the other schedulers, it is driven by the metric given to [`ReduceLROnPlateau::step`],
+/// and reduces the current learning rate of every group of the optimizer.
+#[derive(Debug, Clone)]
+pub struct ReduceLROnPlateau<F = f64> {
+    mode: PlateauMode,
+    factor: F,
+    patience: usize,
+    threshold: F,
+    cooldown: usize,
+    min_lr: F,
+    eps: F,
+
+    best: Option<F>,
+    bad_steps: usize,
+    cooldown_steps: usize,
+}
+
+impl<F: Float> Default for ReduceLROnPlateau<F> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Float> ReduceLROnPlateau<F> {
+    /// Defaults to minimizing the metric, `factor = 0.1`, `patience = 10`, a relative
+    /// `threshold = 1e-4`, no cooldown and no minimum learning rate.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            mode: PlateauMode::Min,
+            factor: F::from_f64(0.1),
+            patience: 10,
+            threshold: F::from_f64(1e-4),
+            cooldown: 0,
+            min_lr: F::zero(),
+            eps: F::from_f64(1e-8),
+            best: None,
+            bad_steps: 0,
+            cooldown_steps: 0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn mode(mut self, mode: PlateauMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn factor(mut self, factor: F) -> Self {
+        assert!(factor < F::one(), "Factor must be smaller than 1");
+        self.factor = factor;
+        self
+    }
+
+    /// Number of steps without improvement tolerated before reducing the learning rate.
+    #[inline]
+    #[must_use]
+    pub fn patience(mut self, patience: usize) -> Self {
+        self.patience = patience;
+        self
+    }
+
+    /// Relative change of the best metric needed to count as an improvement.
+    #[inline]
+    #[must_use]
+    pub fn threshold(mut self, threshold: F) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Number of steps to wait after a reduction before monitoring the metric again.
+    #[inline]
+    #[must_use]
+    pub fn cooldown(mut self, cooldown: usize) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn min_lr(mut self, min_lr: F) -> Self {
+        self.min_lr = min_lr;
+        self
+    }
+
+    #[inline]
+    fn is_better(&self, metric: F, best: F) -> bool {
+        match self.mode {
+            PlateauMode::Min => metric < best * (F::one() - self.threshold),
+            PlateauMode::Max => metric > best * (F::one() + self.threshold),
+        }
+    }
+
+    /// Records the metric of the last epoch, reducing the learning rate of the optimizer if it
+    /// has not improved for too long.
+    pub fn step<O: Optimizer<F>>(&mut self, metric: F, optimizer: &mut O) {
+        match self.best {
+            Some(best) if !self.is_better(metric, best) => self.bad_steps += 1,
+            _ => {
+                self.best = Some(metric);
+                self.bad_steps = 0;
+            }
+        }
+
+        if self.cooldown_steps > 0 {
+            self.cooldown_steps -= 1;
+            self.bad_steps = 0;
+        }
+
+        if self.bad_steps > self.patience {
+            for group in 0..optimizer.num_groups() {
+                let lr = optimizer.group_lr(group);
+                let reduced = (lr * self.factor).max(self.min_lr);
+                if lr - reduced > self.eps {
+                    optimizer.set_group_lr(group, reduced);
+                }
+            }
+            self.cooldown_steps = self.cooldown;
+            self.bad_steps = 0;
+        }
+    }
+
+    /// Best metric and step counts, see [`LrScheduler::state`](super::LrScheduler::state).
+    pub fn state(&self) -> State<F> {
+        let mut state = State::new();
+        if let Some(best) = self.best {
+            state.arrays.insert("best", arr0(best).into_dyn());
+        }
+        let counters = [
+            ("bad_steps", self.bad_steps),
+            ("cooldown_steps", self.cooldown_steps),
+        ];
+        for (name, steps) in counters {
+            state.counters.insert(name.to_string(), steps as u64);
+        }
+        state
+    }
+
+    pub fn load_state(&mut self, state: &State<F>) -> Result<()> {
+        state.check_keys(|name| ["best", "bad_steps", "cooldown_steps"].contains(&name))?;
+        let bad_steps = state.counter("bad_steps")? as usize;
+        let cooldown_steps = state.counter("cooldown_steps")? as usize;
+        self.best = match state.arrays.contains_key("best") {
+            true => Some(state.scalar("best")?),
+            false => None,
+        };
+        self.bad_steps = bad_steps;
+        self.cooldown_steps = cooldown_steps;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SGD;
+
+    #[test]
+    fn resume() {
+        let mut optim = SGD::new(1.0);
+        let mut scheduler = ReduceLROnPlateau::new().patience(2).cooldown(1);
+        for metric in [3.0, 2.0, 2.5] {
+            scheduler.step(metric, &mut optim);
+        }
+
+        let mut resumed = ReduceLROnPlateau::new().patience(2).cooldown(1);
+        resumed.load_state(&scheduler.state()).unwrap();
+        assert_eq!(Some(2.0), resumed.best);
+        assert_eq!(1, resumed.bad_steps);
+        assert_eq!(scheduler.state(), resumed.state());
+    }
+
+    #[test]
+    fn reduces_after_patience() {
+        let mut optim = SGD::new(1.0);
+        let mut scheduler = ReduceLROnPlateau::new().patience(1).factor(0.5);
+
+        let lrs: Vec<_> = [3.0, 2.0, 2.0, 2.0, 1.0, 1.5, 1.5, 1.5]
+            .into_iter()
+            .map(|metric| {
+                scheduler.step(metric, &mut optim);
+                optim.lr()
+            })
+            .collect();
+        assert_eq!(vec![1.0, 1.0, 1.0, 0.5, 0.5, 0.5, 0.25, 0.25], lrs);
+    }
+
+    #[test]
+    fn max_mode_cooldown_and_min_lr() {
+        let mut optim = SGD::new(1.0);
+        let mut scheduler = ReduceLROnPlateau::new()
+            .mode(PlateauMode::Max)
+            .patience(0)
+            .cooldown(1)
+            .factor(0.1)
+            .min_lr(0.05);
+
+        let lrs: Vec<_> = [0.5, 0.6, 0.6, 0.6, 0.6, 0.6]
+            .into_iter()
+            .map(|metric| {
+                scheduler.step(metric, &mut optim);
+                optim.lr()
+            })
+            .collect();
+        assert_eq!(vec![1.0, 1.0, 0.1, 0.1, 0.05, 0.05], lrs);
+    }
+}
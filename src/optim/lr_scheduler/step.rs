@@ -0,0 +1,123 @@
+use super::{closed_form, group_lrs};
+use crate::optim::Optimizer;
+use crate::Float;
+
+/// Multiplies the learning rate by `gamma` every `step_size` steps.
+#[derive(Debug, Clone)]
+pub struct StepLR<F = f64> {
+    base_lrs: Vec<F>,
+    step_size: usize,
+    gamma: F,
+    steps: usize,
+}
+
+impl<F: Float> StepLR<F> {
+    #[inline]
+    #[must_use]
+    pub fn new<O: Optimizer<F>>(optimizer: &O, step_size: usize, gamma: F) -> Self {
+        assert!(step_size > 0, "Step size must be positive");
+        Self {
+            base_lrs: group_lrs(optimizer),
+            step_size,
+            gamma,
+            steps: 0,
+        }
+    }
+
+    #[inline]
+    fn lr_at(&self, base_lr: F, steps: usize) -> F {
+        base_lr * self.gamma.powi((steps / self.step_size) as i32)
+    }
+}
+
+closed_form!(StepLR);
+
+/// Multiplies the learning rate by `gamma` at every milestone.
+#[derive(Debug, Clone)]
+pub struct MultiStepLR<F = f64> {
+    base_lrs: Vec<F>,
+    milestones: Vec<usize>,
+    gamma: F,
+    steps: usize,
+}
+
+impl<F: Float> MultiStepLR<F> {
+    #[inline]
+    #[must_use]
+    pub fn new<O: Optimizer<F>>(optimizer: &O, milestones: &[usize], gamma: F) -> Self {
+        Self {
+            base_lrs: group_lrs(optimizer),
+            milestones: milestones.to_vec(),
+            gamma,
+            steps: 0,
+        }
+    }
+
+    #[inline]
+    fn lr_at(&self, base_lr: F, steps: usize) -> F {
+        let reached = self.milestones.iter().filter(|&&m| m <= steps).count();
+        base_lr * self.gamma.powi(reached as i32)
+    }
+}
+
+closed_form!(MultiStepLR);
+
+/// Multiplies the learning rate by `gamma` every step.
+#[derive(Debug, Clone)]
+pub struct ExponentialLR<F = f64> {
+    base_lrs: Vec<F>,
+    gamma: F,
+    steps: usize,
+}
+
+impl<F: Float> ExponentialLR<F> {
+    #[inline]
+    #[must_use]
+    pub fn new<O: Optimizer<F>>(optimizer: &O, gamma: F) -> Self {
+        Self {
+            base_lrs: group_lrs(optimizer),
+            gamma,
+            steps: 0,
+        }
+    }
+
+    #[inline]
+    fn lr_at(&self, base_lr: F, steps: usize) -> F {
+        base_lr * self.gamma.powi(steps as i32)
+    }
+}
+
+closed_form!(ExponentialLR);
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{assert_close, schedule};
+    use super::*;
+    use crate::optim::LrScheduler;
+    use crate::SGD;
+
+    #[test]
+    fn step_lr() {
+        let mut optim = SGD::new(1.0);
+        let scheduler = StepLR::new(&optim, 2, 0.5);
+        assert_eq!(1.0, scheduler.lr());
+        let lrs = schedule(scheduler, &mut optim, 5);
+        assert_close(&[1.0, 0.5, 0.5, 0.25, 0.25], &lrs);
+    }
+
+    #[test]
+    fn multi_step_lr() {
+        let mut optim = SGD::new(1.0);
+        let scheduler = MultiStepLR::new(&optim, &[1, 4], 0.1);
+        let lrs = schedule(scheduler, &mut optim, 5);
+        assert_close(&[0.1, 0.1, 0.1, 0.01, 0.01], &lrs);
+    }
+
+    #[test]
+    fn exponential_lr() {
+        let mut optim = SGD::new(2.0);
+        let scheduler = ExponentialLR::new(&optim, 0.5);
+        let lrs = schedule(scheduler, &mut optim, 3);
+        assert_close(&[1.0, 0.5, 0.25], &lrs);
+    }
+}
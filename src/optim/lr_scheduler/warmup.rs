@@ -0,0 +1,55 @@
+use super::{closed_form, group_lrs, ClosedForm};
+use crate::optim::Optimizer;
+use crate::Float;
+
+/// Increases the learning rate linearly from `start_factor` times its initial value to the initial
+/// value over `warmup_steps` steps, then keeps it constant.
+#[derive(Debug, Clone)]
+pub struct LinearWarmup<F = f64> {
+    base_lrs: Vec<F>,
+    start_factor: F,
+    warmup_steps: usize,
+    steps: usize,
+}
+
+impl<F: Float> LinearWarmup<F> {
+    /// Sets the learning rates of the optimizer to the initial ones.
+    #[inline]
+    #[must_use]
+    pub fn new<O: Optimizer<F>>(optimizer: &mut O, start_factor: F, warmup_steps: usize) -> Self {
+        assert!(warmup_steps > 0, "Warmup steps must be positive");
+        let scheduler = Self {
+            base_lrs: group_lrs(optimizer),
+            start_factor,
+            warmup_steps,
+            steps: 0,
+        };
+        scheduler.apply(optimizer);
+        scheduler
+    }
+
+    #[inline]
+    fn lr_at(&self, base_lr: F, steps: usize) -> F {
+        let progress =
+            F::from_usize(steps.min(self.warmup_steps)) / F::from_usize(self.warmup_steps);
+        base_lr * (self.start_factor + (F::one() - self.start_factor) * progress)
+    }
+}
+
+closed_form!(LinearWarmup);
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{assert_close, schedule};
+    use super::*;
+    use crate::SGD;
+
+    #[test]
+    fn linear_warmup() {
+        let mut optim = SGD::new(1.0);
+        let scheduler = LinearWarmup::new(&mut optim, 0.25, 3);
+        assert_eq!(0.25, optim.lr());
+        let lrs = schedule(scheduler, &mut optim, 4);
+        assert_close(&[0.5, 0.75, 1.0, 1.0], &lrs);
+    }
+}
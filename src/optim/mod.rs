@@ -1,8 +1,208 @@
+use crate::checkpoint::State;
 use crate::prelude::Module;
+use crate::{Error, Float, Result};
+use ndarray::{arr0, ArrayD};
+use std::collections::HashMap;
 
+mod adam;
+mod clip;
+mod group;
+pub mod lr_scheduler;
+mod rmsprop;
 mod sgd;
+pub use adam::{Adam, AdamW};
+pub use clip::{clip_grad_norm, clip_grad_value, GradClip};
+pub use group::ParamGroup;
+pub use lr_scheduler::LrScheduler;
+pub use rmsprop::RMSprop;
 pub use sgd::SGD;
 
-pub trait Optimizer {
-    fn step<M: Module>(&mut self, module: &mut M);
+/// Updates the parameters of a module from their gradients. Optimizers keeping state between
+/// steps, like the moments of [`Adam`], key it by [`Parameter::name`](crate::module::Parameter),
+/// so the same optimizer should only be used with a single module.
+///
+/// Parameters can be split in [`ParamGroup`]s with their own hyperparameters. Group 0 is the
+/// default group, holding the parameters that are not selected by any other.
+pub trait Optimizer<F: Float = f64> {
+    fn step<M: Module<F>>(&mut self, module: &mut M);
+
+    /// Resets the gradients of the module before the next backward passes, see
+    /// [`Module::zero_grad`].
+    #[inline]
+    fn zero_grad<M: Module<F>>(&self, module: &mut M) {
+        module.zero_grad()
+    }
+
+    /// Number of parameter groups, including the default one.
+    fn num_groups(&self) -> usize;
+
+    fn group_lr(&self, group: usize) -> F;
+
+    /// Changes the learning rate of the next steps of a group, e.g. from an [`LrScheduler`].
+    fn set_group_lr(&mut self, group: usize, lr: F);
+
+    /// Learning rate of the default group.
+    #[inline]
+    fn lr(&self) -> F {
+        self.group_lr(0)
+    }
+
+    /// Changes the learning rate of the default group, and of the groups without their own.
+    #[inline]
+    fn set_lr(&mut self, lr: F) {
+        self.set_group_lr(0, lr)
+    }
+
+    /// Learning rate of every group, as `param_groups.{group}.lr`, and buffers of every
+    /// parameter, as `state.{parameter}.{buffer}`, to resume training from a
+    /// [`Checkpoint`](crate::checkpoint::Checkpoint).
+    fn state(&self) -> State<F>;
+
+    /// Restores the [state](Optimizer::state) of an optimizer created with the same groups, for
+    /// the parameters of `module`. Fails without modifying the optimizer if a value is missing or
+    /// unexpected, or if a buffer does not have the shape of its parameter.
+    fn load_state<M: Module<F>>(&mut self, module: &mut M, state: &State<F>) -> Result<()>;
+}
+
+/// State holding the learning rate of every group.
+fn lr_state<F: Float, O: Optimizer<F>>(optimizer: &O) -> State<F> {
+    let mut state = State::new();
+    for group in 0..optimizer.num_groups() {
+        let lr = arr0(optimizer.group_lr(group)).into_dyn();
+        state.arrays.insert(format!("param_groups.{group}.lr"), lr);
+    }
+    state
+}
+
+/// Splits the `state.{parameter}.{buffer}` names of an optimizer state.
+#[inline]
+fn param_key(name: &str) -> Option<(&str, &str)> {
+    name.strip_prefix("state.")?.rsplit_once('.')
+}
+
+/// Learning rates of a state, after checking that it only holds those of the groups of the
+/// optimizer and the given buffers.
+fn check_state<F: Float, O: Optimizer<F>>(
+    optimizer: &O,
+    state: &State<F>,
+    buffers: &[&str],
+) -> Result<Vec<F>> {
+    let groups = optimizer.num_groups();
+    state.check_keys(|name| match param_key(name) {
+        Some((_, buffer)) => buffers.contains(&buffer),
+        None => name
+            .strip_prefix("param_groups.")
+            .and_then(|name| name.strip_suffix(".lr"))
+            .and_then(|group| group.parse::<usize>().ok())
+            .is_some_and(|group| group < groups),
+    })?;
+    (0..groups)
+        .map(|group| state.scalar(&format!("param_groups.{group}.lr")))
+        .collect()
+}
+
+/// Shape of every parameter of a module, by name.
+fn parameter_shapes<F: Float, M: Module<F>>(module: &mut M) -> HashMap<String, Vec<usize>> {
+    let parameters = module.parameters().iter();
+    parameters
+        .map(|parameter| (parameter.name, parameter.parm.shape().to_vec()))
+        .collect()
+}
+
+/// Array `state.{parameter}.{buffer}` of a state, after checking that it has the shape of the
+/// parameter.
+fn buffer<'a, F: Float>(
+    state: &'a State<F>,
+    shapes: &HashMap<String, Vec<usize>>,
+    parameter: &str,
+    buffer: &str,
+) -> Result<&'a ArrayD<F>> {
+    let key = format!("state.{parameter}.{buffer}");
+    let array = state.array(&key)?;
+    match shapes.get(parameter) {
+        Some(shape) if array.shape() == shape => Ok(array),
+        Some(shape) => Err(Error::ShapeMismatch {
+            name: key,
+            expected: shape.clone(),
+            found: array.shape().to_vec(),
+        }),
+        None => Err(Error::StateDictKeys {
+            missing: vec![],
+            unexpected: vec![key],
+        }),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::Optimizer;
+    use crate::module::{Module, Parameters};
+    use crate::{Error, Result};
+    use ndarray::prelude::*;
+
+    /// Module with a single parameter, whose gradient is set by hand.
+    #[derive(Debug)]
+    pub(crate) struct Constant {
+        pub parm: ArrayD<f64>,
+        pub grad: ArrayD<f64>,
+    }
+
+    impl Constant {
+        pub fn new(parm: ArrayD<f64>, grad: ArrayD<f64>) -> Self {
+            Self { parm, grad }
+        }
+    }
+
+    impl Module for Constant {
+        fn forward(&mut self, input: ArrayD<f64>) -> ArrayD<f64> {
+            input
+        }
+
+        fn backward(&mut self, gradient: ArrayD<f64>) -> Result<ArrayD<f64>> {
+            Ok(gradient)
+        }
+
+        fn parameters(&mut self) -> Parameters<'_> {
+            Parameters::new(1).add("parm", &mut self.parm, &mut self.grad)
+        }
+    }
+
+    /// Checks that resuming from the state after a step gives the same second step.
+    pub(crate) fn resumes<O: Optimizer>(optim: impl Fn() -> O) {
+        let module = || Constant::new(array![1.0, -2.0].into_dyn(), array![0.5, 1.0].into_dyn());
+        let (mut expected, mut interrupted) = (module(), module());
+        let mut uninterrupted = optim();
+        uninterrupted.step(&mut expected);
+        uninterrupted.step(&mut expected);
+
+        let mut first = optim();
+        first.step(&mut interrupted);
+        let state = first.state();
+        let mut resumed = optim();
+        resumed.load_state(&mut interrupted, &state).unwrap();
+        assert_eq!(state, resumed.state());
+        resumed.step(&mut interrupted);
+        assert_eq!(expected.parm, interrupted.parm);
+
+        let mut wrong_shape = state.clone();
+        wrong_shape.arrays = (state.arrays.clone().into_iter())
+            .map(|(key, array)| match key.starts_with("state.") {
+                true => (key, ArrayD::zeros(vec![3])),
+                false => (key, array),
+            })
+            .collect();
+        assert!(matches!(
+            optim().load_state(&mut interrupted, &wrong_shape),
+            Err(Error::ShapeMismatch { .. })
+        ));
+
+        let mut unexpected = state.clone();
+        unexpected
+            .counters
+            .insert("state.parm.unknown".to_string(), 1);
+        assert!(matches!(
+            resumed.load_state(&mut interrupted, &unexpected),
+            Err(Error::StateDictKeys { .. })
+        ));
+    }
 }
@@ -0,0 +1,285 @@
+use super::group::{group_methods, Hyperparameters, ParamGroups};
+use super::{buffer, check_state, lr_state, param_key, parameter_shapes};
+use super::{GradClip, Optimizer, ParamGroup};
+use crate::checkpoint;
+use crate::module::Parameter;
+use crate::prelude::Module;
+use crate::{Float, Result};
+use ndarray::prelude::*;
+use ndarray::Zip;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug)]
+struct State<F> {
+    /// Running average of the squared gradient
+    square_avg: ArrayD<F>,
+    /// Running average of the gradient, only when centered
+    grad_avg: Option<ArrayD<F>>,
+    /// Allocated on the first step with momentum
+    momentum_buffer: Option<ArrayD<F>>,
+}
+
+/// RMSprop, dividing the gradient by a running average of its magnitude.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+pub struct RMSprop<F = f64> {
+    groups: ParamGroups<F>,
+    alpha: F,
+    eps: F,
+    centered: bool,
+    clip: Option<GradClip<F>>,
+    state: HashMap<String, State<F>>,
+}
+
+impl<F: Float> RMSprop<F> {
+    /// Defaults to `alpha = 0.99`, `eps = 1e-8`, no weight decay, no momentum and not centered.
+    #[inline]
+    #[must_use]
+    pub fn new(lr: F) -> Self {
+        Self {
+            groups: ParamGroups::new(lr, F::zero(), F::zero()),
+            alpha: F::from_f64(0.99),
+            eps: F::from_f64(1e-8),
+            centered: false,
+            clip: None,
+            state: HashMap::new(),
+        }
+    }
+
+    /// Smoothing constant of the running averages.
+    #[inline]
+    #[must_use]
+    pub fn alpha(mut self, alpha: F) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn eps(mut self, eps: F) -> Self {
+        self.eps = eps;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn weight_decay(mut self, weight_decay: F) -> Self {
+        self.groups.default.weight_decay = weight_decay;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn momentum(mut self, momentum: F) -> Self {
+        self.groups.default.momentum = momentum;
+        self
+    }
+
+    /// Normalizes the gradient by an estimate of its variance instead of its second moment.
+    #[inline]
+    #[must_use]
+    pub fn centered(mut self, centered: bool) -> Self {
+        self.centered = centered;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn param_group(mut self, group: ParamGroup<F>) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// Clips the gradients before every step.
+    #[inline]
+    #[must_use]
+    pub fn clip_grad(mut self, clip: GradClip<F>) -> Self {
+        self.clip = Some(clip);
+        self
+    }
+
+    fn update(
+        &mut self,
+        Parameter {
+            name, parm, grad, ..
+        }: Parameter<'_, F>,
+    ) {
+        let Self { alpha, eps, .. } = *self;
+        let Hyperparameters {
+            lr,
+            momentum,
+            weight_decay,
+        } = self.groups.get(&name);
+        let state = self.state.entry(name).or_insert_with(|| State {
+            square_avg: ArrayD::zeros(parm.raw_dim()),
+            grad_avg: self.centered.then(|| ArrayD::zeros(parm.raw_dim())),
+            momentum_buffer: None,
+        });
+
+        let mut grad = grad.clone();
+        if weight_decay != F::zero() {
+            grad.scaled_add(weight_decay, parm);
+        }
+
+        Zip::from(&mut state.square_avg)
+            .and(&grad)
+            .for_each(|avg, &g| *avg = alpha * *avg + (F::one() - alpha) * g * g);
+
+        let mut denom = state.square_avg.clone();
+        if let Some(grad_avg) = &mut state.grad_avg {
+            Zip::from(&mut *grad_avg)
+                .and(&grad)
+                .for_each(|avg, &g| *avg = alpha * *avg + (F::one() - alpha) * g);
+            denom -= &grad_avg.mapv(|g| g * g);
+        }
+        // Normalized step
+        Zip::from(&mut grad)
+            .and(&denom)
+            .for_each(|g, &d| *g /= d.sqrt() + eps);
+
+        if momentum == F::zero() {
+            parm.scaled_add(-lr, &grad);
+        } else {
+            let buffer = state
+                .momentum_buffer
+                .get_or_insert_with(|| ArrayD::zeros(parm.raw_dim()));
+            *buffer *= momentum;
+            *buffer += &grad;
+            parm.scaled_add(-lr, buffer);
+        }
+    }
+}
+
+impl<F: Float> Optimizer<F> for RMSprop<F> {
+    fn step<M: Module<F>>(&mut self, module: &mut M) {
+        if let Some(clip) = &self.clip {
+            clip.apply(module);
+        }
+        module
+            .parameters()
+            .trainable()
+            .for_each(|parameter| self.update(parameter));
+    }
+
+    group_methods!();
+
+    fn state(&self) -> checkpoint::State<F> {
+        let mut state = lr_state(self);
+        for (name, parameter) in &self.state {
+            let key = |buffer| format!("state.{name}.{buffer}");
+            let buffers = [
+                ("square_avg", Some(&parameter.square_avg)),
+                ("grad_avg", parameter.grad_avg.as_ref()),
+                ("momentum_buffer", parameter.momentum_buffer.as_ref()),
+            ];
+            for (buffer, array) in buffers {
+                if let Some(array) = array {
+                    state.arrays.insert(key(buffer), array.clone());
+                }
+            }
+        }
+        state
+    }
+
+    /// The gradient averages are only expected when centered.
+    fn load_state<M: Module<F>>(
+        &mut self,
+        module: &mut M,
+        state: &checkpoint::State<F>,
+    ) -> Result<()> {
+        let buffers: &[_] = match self.centered {
+            true => &["square_avg", "grad_avg", "momentum_buffer"],
+            false => &["square_avg", "momentum_buffer"],
+        };
+        let lrs = check_state(self, state, buffers)?;
+        let shapes = parameter_shapes(module);
+        let names: BTreeSet<_> = state
+            .arrays
+            .keys()
+            .filter_map(|key| Some(param_key(key)?.0))
+            .collect();
+        let parameters = names
+            .into_iter()
+            .map(|name| {
+                let array = |key| buffer(state, &shapes, name, key).cloned();
+                let grad_avg = match self.centered {
+                    true => Some(array("grad_avg")?),
+                    false => None,
+                };
+                let momentum_key = format!("state.{name}.momentum_buffer");
+                let parameter = State {
+                    square_avg: array("square_avg")?,
+                    grad_avg,
+                    momentum_buffer: match state.arrays.contains_key(&momentum_key) {
+                        true => Some(array("momentum_buffer")?),
+                        false => None,
+                    },
+                };
+                Ok((name.to_string(), parameter))
+            })
+            .collect::<Result<_>>()?;
+
+        self.state = parameters;
+        for (group, lr) in lrs.into_iter().enumerate() {
+            self.groups.set_lr(group, lr);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_array_eq;
+    use crate::optim::tests::{resumes, Constant};
+    use crate::Linear;
+
+    #[test]
+    fn first_step() {
+        // sqrt(square_avg) = sqrt(1 - alpha) * |g| = 0.1 * |g|
+        let mut optim = RMSprop::new(0.01);
+        let mut module = Constant::new(array![1.0, 2.0].into_dyn(), array![3.0, -0.5].into_dyn());
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.9, 2.1].into_dyn());
+    }
+
+    #[test]
+    fn momentum_and_centered() {
+        let mut optim = RMSprop::new(0.01).alpha(0.75).momentum(0.5).centered(true);
+        let mut module = Constant::new(array![0.0].into_dyn(), array![2.0].into_dyn());
+
+        // square_avg = 1, grad_avg = 0.5, step = 2 / sqrt(1 - 0.25) = 4 / sqrt(3)
+        optim.step(&mut module);
+        let first = 4.0 / 3f64.sqrt();
+        assert_array_eq!(module.parm, array![-0.01 * first].into_dyn());
+
+        // square_avg = 1.75, grad_avg = 0.875, buffer = 0.5 * first + step
+        optim.step(&mut module);
+        let second = 2.0 / (1.75f64 - 0.875 * 0.875).sqrt();
+        let expected = -0.01 * first - 0.01 * (0.5 * first + second);
+        assert_array_eq!(module.parm, array![expected].into_dyn());
+    }
+
+    #[test]
+    fn fits_linear_regression() {
+        // y = 2x - 1
+        let x = array![[-1.0], [0.0], [1.0], [2.0]].into_dyn();
+        let y = array![[-3.0], [-1.0], [1.0], [3.0]].into_dyn();
+        let mut module = Linear::new(1, 1);
+        let mut optim = RMSprop::new(0.01f64);
+
+        for _ in 0..1000 {
+            optim.zero_grad(&mut module);
+            let pred = module.forward(x.clone());
+            module.backward(pred - &y).unwrap();
+            optim.step(&mut module);
+        }
+        let pred = module.forward(x);
+        assert_array_eq!(pred, y, 5e-2);
+    }
+
+    #[test]
+    fn resume() {
+        resumes(|| RMSprop::new(0.01).centered(true).momentum(0.9));
+    }
+}
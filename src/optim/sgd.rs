@@ -1,44 +1,393 @@
-use super::Optimizer;
+use super::group::{group_methods, Hyperparameters, ParamGroups};
+use super::{buffer, check_state, lr_state, param_key, parameter_shapes};
+use super::{GradClip, Optimizer, ParamGroup};
+use crate::checkpoint::State;
 use crate::module::Parameter;
 use crate::prelude::Module;
+use crate::{Float, Result};
+use ndarray::prelude::*;
+use ndarray::Zip;
+use std::collections::HashMap;
 
+/// Stochastic gradient descent, optionally with momentum, Nesterov momentum and weight decay.
+/// Parameters are updated in place.
 #[allow(clippy::upper_case_acronyms)]
-pub struct SGD {
-    lr: f64,
+#[derive(Debug)]
+pub struct SGD<F = f64> {
+    groups: ParamGroups<F>,
+    dampening: F,
+    nesterov: bool,
+    maximize: bool,
+    clip: Option<GradClip<F>>,
+    /// Momentum buffer of every parameter, by name
+    state: HashMap<String, ArrayD<F>>,
 }
 
-impl SGD {
+impl<F: Float> SGD<F> {
+    /// Plain gradient descent, without momentum nor weight decay.
     #[inline]
     #[must_use]
-    pub fn new(lr: f64) -> Self {
-        Self { lr }
+    pub fn new(lr: F) -> Self {
+        Self {
+            groups: ParamGroups::new(lr, F::zero(), F::zero()),
+            dampening: F::zero(),
+            nesterov: false,
+            maximize: false,
+            clip: None,
+            state: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn momentum(mut self, momentum: F) -> Self {
+        self.groups.default.momentum = momentum;
+        self
+    }
+
+    /// Scales down the gradient added to the momentum buffer by `1 - dampening`.
+    #[inline]
+    #[must_use]
+    pub fn dampening(mut self, dampening: F) -> Self {
+        self.dampening = dampening;
+        self
+    }
+
+    /// L2 penalty added to the gradient.
+    #[inline]
+    #[must_use]
+    pub fn weight_decay(mut self, weight_decay: F) -> Self {
+        self.groups.default.weight_decay = weight_decay;
+        self
+    }
+
+    /// Nesterov momentum, which requires a momentum and no dampening.
+    ///
+    /// # Panics
+    ///
+    /// On [`step`](Optimizer::step), when a group, or the optimizer itself, has no momentum, or
+    /// when the dampening is not zero.
+    #[inline]
+    #[must_use]
+    pub fn nesterov(mut self, nesterov: bool) -> Self {
+        self.nesterov = nesterov;
+        self
+    }
+
+    /// Maximizes the objective instead of minimizing it.
+    #[inline]
+    #[must_use]
+    pub fn maximize(mut self, maximize: bool) -> Self {
+        self.maximize = maximize;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn param_group(mut self, group: ParamGroup<F>) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// Clips the gradients before every step.
+    #[inline]
+    #[must_use]
+    pub fn clip_grad(mut self, clip: GradClip<F>) -> Self {
+        self.clip = Some(clip);
+        self
+    }
+
+    /// Checks the hyperparameters required by Nesterov momentum, when enabled.
+    #[inline]
+    fn check_nesterov(&self) {
+        if !self.nesterov {
+            return;
+        }
+        assert!(
+            self.groups.momentums().all(|momentum| momentum > F::zero()),
+            "Nesterov momentum requires a momentum"
+        );
+        assert!(
+            self.dampening == F::zero(),
+            "Nesterov momentum requires zero dampening"
+        );
+    }
+
+    fn update(
+        &mut self,
+        Parameter {
+            name, parm, grad, ..
+        }: Parameter<'_, F>,
+    ) {
+        let Self {
+            dampening,
+            nesterov,
+            maximize,
+            ..
+        } = *self;
+        let Hyperparameters {
+            lr,
+            momentum,
+            weight_decay,
+        } = self.groups.get(&name);
+        // Maximizing ascends the gradient, but still decays the weights
+        let sign = if maximize { -F::one() } else { F::one() };
+
+        if momentum == F::zero() {
+            if weight_decay == F::zero() {
+                parm.scaled_add(-lr * sign, grad);
+            } else {
+                Zip::from(&mut *parm)
+                    .and(&*grad)
+                    .for_each(|p, &g| *p -= lr * (sign * g + weight_decay * *p));
+            }
+            return;
+        }
+
+        // The buffer starts as the first gradient, without dampening
+        let first = !self.state.contains_key(&name);
+        let buffer = self
+            .state
+            .entry(name)
+            .or_insert_with(|| ArrayD::zeros(parm.raw_dim()));
+        let (decay, keep) = match first {
+            true => (F::one(), F::zero()),
+            false => (F::one() - dampening, momentum),
+        };
+
+        Zip::from(&mut *parm)
+            .and(&*grad)
+            .and(buffer)
+            .for_each(|p, &g, b| {
+                let g = sign * g + weight_decay * *p;
+                *b = keep * *b + decay * g;
+                let step = if nesterov { g + momentum * *b } else { *b };
+                *p -= lr * step;
+            });
     }
 }
 
-impl Optimizer for SGD {
-    fn step<M: Module>(&mut self, module: &mut M) {
+impl<F: Float> Optimizer<F> for SGD<F> {
+    fn step<M: Module<F>>(&mut self, module: &mut M) {
+        self.check_nesterov();
+        if let Some(clip) = &self.clip {
+            clip.apply(module);
+        }
         module
             .parameters()
-            .iter()
-            .for_each(|Parameter { parm, grad }| *parm = parm.clone() - (self.lr * grad.clone()))
+            .trainable()
+            .for_each(|parameter| self.update(parameter));
+    }
+
+    group_methods!();
+
+    fn state(&self) -> State<F> {
+        let mut state = lr_state(self);
+        for (name, buffer) in &self.state {
+            let key = format!("state.{name}.momentum_buffer");
+            state.arrays.insert(key, buffer.clone());
+        }
+        state
+    }
+
+    fn load_state<M: Module<F>>(&mut self, module: &mut M, state: &State<F>) -> Result<()> {
+        let lrs = check_state(self, state, &["momentum_buffer"])?;
+        let shapes = parameter_shapes(module);
+        self.state = state
+            .arrays
+            .keys()
+            .filter_map(|key| Some(param_key(key)?.0))
+            .map(|name| {
+                let momentum_buffer = buffer(state, &shapes, name, "momentum_buffer")?;
+                Ok((name.to_string(), momentum_buffer.clone()))
+            })
+            .collect::<Result<_>>()?;
+        for (group, lr) in lrs.into_iter().enumerate() {
+            self.groups.set_lr(group, lr);
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use ndarray::Array;
+    use ndarray::ArrayD;
 
     use super::*;
-    use crate::Linear;
+    use crate::optim::tests::{resumes, Constant};
+    use crate::{assert_array_eq, sequential, Linear, Sequential};
 
     #[test]
     fn optimize() {
         let mut optim = SGD::new(0.1);
         let mut linear = Linear::new(2, 2);
 
-        linear.forward(Array::zeros((2, 2)));
-        linear.backward(Array::zeros((2, 2)));
+        linear.forward(ArrayD::zeros(vec![2, 2]));
+        linear.backward(ArrayD::zeros(vec![2, 2])).unwrap();
+
+        optim.step(&mut linear);
+    }
+
+    #[test]
+    fn optimize_f32() {
+        let mut optim = SGD::new(0.1f32);
+        let mut linear = Linear::new(2, 2);
+
+        linear.forward(ArrayD::zeros(vec![2, 2]));
+        linear.backward(ArrayD::ones(vec![2, 2])).unwrap();
 
+        let bias = linear.parameters().iter().nth(1).unwrap().parm.clone();
         optim.step(&mut linear);
+        let updated = linear.parameters().iter().nth(1).unwrap().parm.clone();
+        let expected = bias - 0.1;
+        crate::assert_array_eq!(updated, expected);
+    }
+
+    fn constant() -> Constant {
+        Constant::new(array![1.0, -2.0].into_dyn(), array![1.0, 0.5].into_dyn())
+    }
+
+    #[test]
+    fn weight_decay_and_maximize() {
+        let mut module = constant();
+        let mut optim = SGD::new(0.1).weight_decay(0.5);
+        optim.step(&mut module);
+        // g = [1.5, -0.5]
+        assert_array_eq!(module.parm, array![0.85, -1.95].into_dyn());
+
+        let mut module = constant();
+        let mut optim = SGD::new(0.1).maximize(true).weight_decay(0.5);
+        optim.step(&mut module);
+        // g = [-0.5, -1.5]
+        assert_array_eq!(module.parm, array![1.05, -1.85].into_dyn());
+
+        let mut module = constant();
+        let mut optim = SGD::new(0.1).maximize(true).momentum(0.5);
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![1.1, -1.95].into_dyn());
+    }
+
+    #[test]
+    fn momentum() {
+        let mut module = constant();
+        let mut optim = SGD::new(0.1).momentum(0.9).dampening(0.5);
+
+        // buffer = g on the first step, then 0.9 * buffer + 0.5 * g
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.9, -2.05].into_dyn());
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.76, -2.12].into_dyn());
+    }
+
+    #[test]
+    fn nesterov() {
+        let mut module = constant();
+        let mut optim = SGD::new(0.1).nesterov(true).momentum(0.9);
+
+        // step = g + 0.9 * buffer, with buffer = [1, 0.5] then [1.9, 0.95]
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.81, -2.095].into_dyn());
+        optim.step(&mut module);
+        assert_array_eq!(module.parm, array![0.539, -2.2305].into_dyn());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nesterov momentum requires a momentum")]
+    fn nesterov_requires_momentum() {
+        SGD::new(0.1).nesterov(true).step(&mut constant());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nesterov momentum requires zero dampening")]
+    fn nesterov_requires_no_dampening() {
+        let mut optim = SGD::new(0.1).momentum(0.9).nesterov(true).dampening(0.1);
+        optim.step(&mut constant());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nesterov momentum requires a momentum")]
+    fn nesterov_requires_group_momentum() {
+        let group = ParamGroup::new("frozen", |name| name.starts_with("0.")).momentum(0.0);
+        let mut optim = SGD::new(0.1)
+            .momentum(0.9)
+            .nesterov(true)
+            .param_group(group);
+        optim.step(&mut constant());
+    }
+
+    #[test]
+    fn param_groups() {
+        let mut module = Sequential::new(vec![
+            Box::new(constant()),
+            Box::new(constant()),
+            Box::new(constant()),
+        ]);
+        let mut optim = SGD::new(0.1)
+            .weight_decay(0.5)
+            .param_group(
+                ParamGroup::new("head", |name| name.starts_with("2."))
+                    .lr(0.01)
+                    .weight_decay(0.0),
+            )
+            .param_group(
+                ParamGroup::new("momentum", |name| name.starts_with("1."))
+                    .weight_decay(0.0)
+                    .momentum(0.9),
+            );
+        assert_eq!(3, optim.num_groups());
+        assert_eq!(0.01, optim.group_lr(1));
+        assert_eq!(0.1, optim.group_lr(2));
+
+        optim.step(&mut module);
+        optim.step(&mut module);
+        let parms: Vec<_> = module.parameters().iter().map(|p| p.parm[0]).collect();
+        // Only the default group decays, and only the second group has momentum
+        let decayed = 1.0 - 0.1 * 1.5;
+        let decayed = decayed - 0.1 * (1.0 + 0.5 * decayed);
+        let expected = [decayed, 1.0 - 0.1 - 0.1 * 1.9, 1.0 - 0.01 * 2.0];
+        for (parm, expected) in parms.into_iter().zip(expected) {
+            assert!((parm - expected).abs() < 1e-9, "{parm} != {expected}");
+        }
+    }
+
+    #[test]
+    fn resume() {
+        resumes(|| SGD::new(0.1).momentum(0.9).nesterov(true));
+    }
+
+    #[test]
+    fn freeze() {
+        let mut module: Sequential = sequential!(Linear(2, 3), Linear(3, 1));
+        module.children()[0].1.freeze();
+        let trainable: Vec<_> = module.parameters().trainable().map(|p| p.name).collect();
+        assert_eq!(vec!["1.weight", "1.bias"], trainable);
+
+        let before = module.state_dict();
+        module.forward(ArrayD::ones(vec![4, 2]));
+        module.backward(ArrayD::ones(vec![4, 1])).unwrap();
+        SGD::new(0.1).step(&mut module);
+        let after = module.state_dict();
+        for (name, parm) in after.iter() {
+            assert_eq!(
+                name.starts_with("0."),
+                parm == before.get(name).unwrap(),
+                "{name}"
+            );
+        }
+        // No gradient is computed for the frozen layer
+        assert!(module
+            .parameters()
+            .iter()
+            .take(2)
+            .all(|p| p.grad.sum() == 0.0));
+
+        module.unfreeze();
+        assert_eq!(4, module.parameters().trainable().count());
+    }
+
+    #[test]
+    #[should_panic(expected = "parm cannot be frozen")]
+    fn freeze_requires_flag() {
+        constant().freeze();
     }
 }
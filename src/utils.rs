@@ -1,6 +1,7 @@
 use std::ops::{Div, Sub};
 
 use ndarray::prelude::*;
+use ndarray::RemoveAxis;
 
 pub fn normalize_zero_one<A, D, R>(x: Array<A, D>) -> Array<R, D>
 where
@@ -41,7 +42,7 @@ pub fn one_hot<A: Into<usize>>(x: Array0<A>, n: usize) -> Array1<f64> {
     encoded
 }
 
-pub fn argmax(arr: Array2<f64>, axis: Axis) -> Option<Array1<usize>> {
+pub fn argmax<A: PartialOrd, D: RemoveAxis>(arr: Array<A, D>, axis: Axis) -> Option<Array1<usize>> {
     arr.axis_iter(axis)
         .map(|v| {
             v.into_iter()
@@ -52,7 +53,7 @@ pub fn argmax(arr: Array2<f64>, axis: Axis) -> Option<Array1<usize>> {
         .collect()
 }
 
-pub fn accuracy(pred: Array2<f64>, truth: Array2<f64>) -> f64 {
+pub fn accuracy<A: PartialOrd, D: RemoveAxis>(pred: Array<A, D>, truth: Array<A, D>) -> f64 {
     let pred = argmax(pred, Axis(0)).unwrap();
     let truth = argmax(truth, Axis(0)).unwrap();
 
@@ -36,7 +36,10 @@ fn training() {
         let n = data_loader.len() as f64;
         let mut total_loss = 0.0;
         let mut total_acc = 0.0;
-        for (x, y) in data_loader.iter_array() {
+        for batch in data_loader.iter_array() {
+            let (x, y) = batch.unwrap();
+            let (x, y) = (x.into_dyn(), y.into_dyn());
+            optim.zero_grad(&mut model);
             let pred = model.forward(x);
             let l = loss.forward(pred.clone(), y.clone());
             let acc = accuracy(pred, y);
@@ -44,7 +47,7 @@ fn training() {
             total_loss += l;
             total_acc += acc;
 
-            model.backward(loss.backward());
+            model.backward(loss.backward().unwrap()).unwrap();
             optim.step(&mut model);
         }
 